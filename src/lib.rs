@@ -1,24 +1,146 @@
 //! Rust bindings for [bombhopper.io](bombhoppper.io).
+//!
+//! Coordinates follow the editor, so the y axis points down. Winding orders
+//! such as "counter-clockwise" describe how a polygon looks on screen.
+//!
+//! # Features
+//!
+//! The `std` feature is enabled by default. Without it the crate only needs
+//! `alloc`, and levels can still be built, serialized with [`Level::to_json`]
+//! and parsed with [`Level::from_json`]. The following need `std`:
+//!
+//! - file and writer I/O: `Level::save`, `Level::load`, `Level::write_to`,
+//!   `Level::write_to_pretty` and `LoadError::Io`
+//! - `Level::to_json_compact_numbers`, which writes through serde_json's
+//!   `io::Write` based serializer
+//! - anything using `sqrt`, trigonometry or rounding: `Point::distance`,
+//!   `Point::length`, `Point::normalize`, `Point::rotate`,
+//!   `Point::rotate_around`, `Shape::regular_polygon`, `Shape::rotate`,
+//!   `Shape::to_polygon`, `Shape::perimeter`, `Entity::rotate`,
+//!   `Level::rotate_all`, `Group::rotate`, `Entity::snap_to_grid`,
+//!   `Level::snap_to_grid`, `Entity::set_angle_radians`, `Shape::offset` and
+//!   `Entity::outline`
+//! - `Level::count_by_type`, which returns a `HashMap`
 
-use std::{
-    collections::HashMap,
-    ops::{Add, Mul, Sub},
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+use alloc::{
+    collections::BTreeMap,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
 };
+use core::{
+    error::Error,
+    fmt,
+    ops::{Add, AddAssign, Div, Index, IndexMut, Mul, MulAssign, Neg, Sub, SubAssign},
+};
+#[cfg(feature = "std")]
+use std::{collections::HashMap, fs, io, path::Path};
 
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, PartialEq, Default, Clone, Copy)]
+#[derive(Serialize, Deserialize, PartialEq, Default, Clone, Copy, Debug)]
 pub struct Point {
     pub x: f32,
     pub y: f32,
 }
 
 impl Point {
-    pub fn new(x: f32, y: f32) -> Self {
+    pub const ZERO: Point = Point::new(0.0, 0.0);
+    pub const ONE: Point = Point::new(1.0, 1.0);
+
+    pub const fn new(x: f32, y: f32) -> Self {
+        Self { x, y }
+    }
+    pub fn x(&self) -> f32 {
+        self.x
+    }
+    pub fn y(&self) -> f32 {
+        self.y
+    }
+    /// Euclidean distance between two points
+    #[cfg(feature = "std")]
+    pub fn distance(&self, other: &Point) -> f32 {
+        (*self - *other).length()
+    }
+    /// Squared distance between two points, avoids the `sqrt` in [`Point::distance`]
+    pub fn distance_squared(&self, other: &Point) -> f32 {
+        (*self - *other).length_squared()
+    }
+    /// Point halfway between two points
+    pub fn midpoint(&self, other: &Point) -> Point {
+        self.lerp(other, 0.5)
+    }
+    /// Length of the vector from the origin
+    #[cfg(feature = "std")]
+    pub fn length(&self) -> f32 {
+        self.length_squared().sqrt()
+    }
+    /// Squared length, avoids the `sqrt` in [`Point::length`]
+    pub fn length_squared(&self) -> f32 {
+        self.dot(self)
+    }
+    /// Vector with the same direction and a length of 1, the zero vector is
+    /// returned unchanged
+    #[cfg(feature = "std")]
+    pub fn normalize(&self) -> Point {
+        let length = self.length();
+        if length == 0.0 {
+            *self
+        } else {
+            *self / length
+        }
+    }
+    /// Dot product of two vectors
+    pub fn dot(&self, other: &Point) -> f32 {
+        self.x * other.x + self.y * other.y
+    }
+    /// Scalar 2D cross product `x * other.y - y * other.x`, negative when `other` is
+    /// counter-clockwise from `self` on screen
+    pub fn cross(&self, other: &Point) -> f32 {
+        self.x * other.y - self.y * other.x
+    }
+    /// Rotates about the origin by `radians`, using the standard rotation matrix
+    /// so `(1, 0)` rotated by `PI / 2` becomes `(0, 1)`, which is clockwise on screen
+    #[cfg(feature = "std")]
+    pub fn rotate(&self, radians: f32) -> Point {
+        let (sin, cos) = radians.sin_cos();
+        Point {
+            x: self.x * cos - self.y * sin,
+            y: self.x * sin + self.y * cos,
+        }
+    }
+    /// Rotates about `center` by `radians`, see [`Point::rotate`]
+    #[cfg(feature = "std")]
+    pub fn rotate_around(&self, center: &Point, radians: f32) -> Point {
+        (*self - *center).rotate(radians) + *center
+    }
+    /// Whether both coordinates are within `epsilon` of `other`'s
+    pub fn approx_eq(&self, other: &Point, epsilon: f32) -> bool {
+        (self.x - other.x).abs() <= epsilon && (self.y - other.y).abs() <= epsilon
+    }
+    /// Linear interpolation towards `other`, `t` isn't clamped so values outside
+    /// `0.0..=1.0` extrapolate
+    pub fn lerp(&self, other: &Point, t: f32) -> Point {
+        *self + (*other - *self) * t
+    }
+}
+
+impl From<(f32, f32)> for Point {
+    fn from((x, y): (f32, f32)) -> Self {
         Self { x, y }
     }
 }
 
+impl From<Point> for (f32, f32) {
+    fn from(point: Point) -> Self {
+        (point.x, point.y)
+    }
+}
+
 impl Add for Point {
     type Output = Self;
 
@@ -52,7 +174,48 @@ impl Mul<f32> for Point {
     }
 }
 
-#[derive(Serialize, Clone, Copy)]
+impl AddAssign for Point {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl SubAssign for Point {
+    fn sub_assign(&mut self, other: Self) {
+        *self = *self - other;
+    }
+}
+
+impl MulAssign<f32> for Point {
+    fn mul_assign(&mut self, other: f32) {
+        *self = *self * other;
+    }
+}
+
+/// Dividing by zero follows `f32` semantics and yields infinities or NaN
+impl Div<f32> for Point {
+    type Output = Self;
+
+    fn div(self, other: f32) -> Self::Output {
+        Point {
+            x: self.x / other,
+            y: self.y / other,
+        }
+    }
+}
+
+impl Neg for Point {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Point {
+            x: -self.x,
+            y: -self.y,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Copy, Debug)]
 #[serde(rename_all = "camelCase")]
 pub enum AmmoType {
     Empty,
@@ -61,51 +224,856 @@ pub enum AmmoType {
     Grenade,
 }
 
-#[derive(Serialize, Clone)]
+impl AmmoType {
+    /// Parses `b`, `g` or `e` in either case. Errors report `index` 0.
+    pub fn from_char(c: char) -> Result<Self, AmmoParseError> {
+        match c.to_ascii_lowercase() {
+            'b' => Ok(Self::Bomb),
+            'g' => Ok(Self::Grenade),
+            'e' => Ok(Self::Empty),
+            _ => Err(AmmoParseError::UnknownChar { ch: c, index: 0 }),
+        }
+    }
+    /// Lowercase character used in ammo sequences
+    pub fn to_char(&self) -> char {
+        match self {
+            Self::Bomb => 'b',
+            Self::Grenade => 'g',
+            Self::Empty => 'e',
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
 pub enum Ammo {
     #[serde(rename = "infiniteAmmo")]
     Infinite(AmmoType),
 
-    /// Note that finite is reversed, so if an ammo is in front, it'll be fired last
+    /// Note that finite is reversed, so if an ammo is in front, it'll be fired last.
+    /// Deserialized magazines are kept in this stored order as-is.
     #[serde(rename = "magazine")]
     Finite(Vec<AmmoType>),
 }
 
+/// Error returned when parsing an ammo sequence
+#[derive(Debug, PartialEq, Eq)]
+pub enum AmmoParseError {
+    /// `ch` at character `index` of the input isn't an ammo type
+    UnknownChar { ch: char, index: usize },
+    /// The count starting at character `index` isn't followed by an ammo type
+    DanglingCount { index: usize },
+    /// The count starting at character `index` is above 65536
+    CountTooLarge { index: usize },
+}
+
+impl fmt::Display for AmmoParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownChar { ch, index } => {
+                write!(f, "unknown ammo type {:?} at index {}", ch, index)
+            }
+            Self::DanglingCount { index } => {
+                write!(f, "count at index {} has no ammo type after it", index)
+            }
+            Self::CountTooLarge { index } => {
+                write!(f, "count at index {} is too large", index)
+            }
+        }
+    }
+}
+
+impl Error for AmmoParseError {}
+
 impl Ammo {
-    pub fn finite_seq(s: &str) -> Result<Self, String> {
+    /// Builds a magazine from ammo types in firing order, so the first element is
+    /// fired first, the same order [`Ammo::finite_seq`] takes
+    pub fn finite(mut types: Vec<AmmoType>) -> Self {
+        types.reverse();
+        Self::Finite(types)
+    }
+    /// Builds a magazine from `(type, count)` runs in firing order, so
+    /// `[(Bomb, 2), (Grenade, 1)]` fires two bombs then a grenade
+    pub fn finite_runs(runs: &[(AmmoType, u32)]) -> Self {
+        Self::finite(
+            runs.iter()
+                .flat_map(|&(ammo, n)| core::iter::repeat_n(ammo, n as usize))
+                .collect(),
+        )
+    }
+    /// Parses a magazine in firing order, `b` for bomb, `g` for grenade and `e`
+    /// for empty. An ammo type may be preceded by a count, so `"3b2ge"` is three
+    /// bombs, two grenades then an empty slot.
+    pub fn finite_seq(s: &str) -> Result<Self, AmmoParseError> {
+        const MAX_COUNT: usize = 1 << 16;
+
         let mut mag = vec![];
-        for c in s.chars().rev() {
-            mag.push(match c.to_ascii_lowercase() {
-                'b' => AmmoType::Bomb,
-                'g' => AmmoType::Grenade,
-                'e' => AmmoType::Empty,
-                // TODO potentially migrate to Error enum
-                _ => return Err(String::from("Ammo Doesn't exist")),
-            })
+        // Start index and value of the count being read
+        let mut count: Option<(usize, usize)> = None;
+        for (index, ch) in s.chars().enumerate() {
+            if let Some(digit) = ch.to_digit(10) {
+                let (start, n) = count.unwrap_or((index, 0));
+                let n = n * 10 + digit as usize;
+                if n > MAX_COUNT {
+                    return Err(AmmoParseError::CountTooLarge { index: start });
+                }
+                count = Some((start, n));
+                continue;
+            }
+            let ammo =
+                AmmoType::from_char(ch).map_err(|_| AmmoParseError::UnknownChar { ch, index })?;
+            let n = count.take().map_or(1, |(_, n)| n);
+            mag.extend(core::iter::repeat_n(ammo, n));
+        }
+        if let Some((index, _)) = count {
+            return Err(AmmoParseError::DanglingCount { index });
         }
+        mag.reverse();
         Ok(Self::Finite(mag))
     }
+    /// Ammo in the order it'll be fired, undoing the reversed storage, or
+    /// `None` for infinite ammo
+    pub fn firing_order(&self) -> Option<Vec<&AmmoType>> {
+        match self {
+            Self::Infinite(_) => None,
+            Self::Finite(mag) => Some(mag.iter().rev().collect()),
+        }
+    }
+    /// Number of shots in the magazine, or `None` for infinite ammo
+    pub fn len(&self) -> Option<usize> {
+        match self {
+            Self::Infinite(_) => None,
+            Self::Finite(mag) => Some(mag.len()),
+        }
+    }
+    /// Whether the magazine is empty, infinite ammo never is
+    pub fn is_empty(&self) -> bool {
+        self.len() == Some(0)
+    }
+    /// Whether the player never runs out of ammo
+    pub fn is_infinite(&self) -> bool {
+        matches!(self, Self::Infinite(_))
+    }
+    /// Inverse of [`Ammo::finite_seq`], giving the lowercase sequence in user
+    /// order or `None` for infinite ammo. Counts are always written out, so
+    /// `"3b"` comes back as `"bbb"`.
+    pub fn to_seq(&self) -> Option<String> {
+        match self {
+            Self::Infinite(_) => None,
+            Self::Finite(mag) => Some(mag.iter().rev().map(AmmoType::to_char).collect()),
+        }
+    }
+}
+
+/// Error returned by [`Color::from_hex`]
+#[derive(Debug, PartialEq, Eq)]
+pub enum ColorError {
+    /// The string didn't have exactly six hex digits
+    InvalidLength { len: usize },
+    /// `ch` at character `index` isn't a hex digit
+    InvalidDigit { ch: char, index: usize },
+}
+
+impl fmt::Display for ColorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidLength { len } => {
+                write!(f, "expected 6 hex digits, found {}", len)
+            }
+            Self::InvalidDigit { ch, index } => {
+                write!(f, "invalid hex digit {:?} at index {}", ch, index)
+            }
+        }
+    }
+}
+
+impl Error for ColorError {}
+
+/// An RGB color, serialized as the packed integer the editor uses for `fillColor`
+#[derive(Serialize, Deserialize, PartialEq, Eq, Default, Clone, Copy, Debug)]
+#[serde(into = "i32", from = "i32")]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Color {
+    pub const WHITE: Color = Color::rgb(255, 255, 255);
+    pub const BLACK: Color = Color::rgb(0, 0, 0);
+
+    pub const fn rgb(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b }
+    }
+
+    /// Parses a `"#rrggbb"` or `"rrggbb"` hex string
+    pub fn from_hex(s: &str) -> Result<Self, ColorError> {
+        let digits = s.strip_prefix('#').unwrap_or(s);
+        let offset = s.len() - digits.len();
+        if let Some((index, ch)) = digits
+            .chars()
+            .enumerate()
+            .find(|(_, c)| !c.is_ascii_hexdigit())
+        {
+            return Err(ColorError::InvalidDigit {
+                ch,
+                index: index + offset,
+            });
+        }
+        if digits.len() != 6 {
+            return Err(ColorError::InvalidLength { len: digits.len() });
+        }
+        let channel = |i: usize| u8::from_str_radix(&digits[i..i + 2], 16).unwrap();
+        Ok(Self::rgb(channel(0), channel(2), channel(4)))
+    }
+
+    /// Packs the color into `0xRRGGBB`
+    pub const fn to_packed(&self) -> i32 {
+        (self.r as i32) << 16 | (self.g as i32) << 8 | self.b as i32
+    }
+}
+
+impl From<Color> for i32 {
+    fn from(color: Color) -> Self {
+        color.to_packed()
+    }
 }
 
-#[derive(Serialize, Clone)]
-#[serde(untagged)]
+impl From<i32> for Color {
+    /// Unpacks `0xRRGGBB`, ignoring any higher bits
+    fn from(packed: i32) -> Self {
+        Self::rgb((packed >> 16) as u8, (packed >> 8) as u8, packed as u8)
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+#[serde(untagged, try_from = "ShapeParams")]
 pub enum Shape {
     Polygon { vertices: Vec<Point> },
     Circle { x: f32, y: f32, radius: f32 },
 }
 
-#[derive(Serialize, Clone)]
+/// Deserializes as a struct rather than untagged, so when flattened into an
+/// entity only these keys are claimed and the rest are left for `extra`
+#[derive(Deserialize)]
+struct ShapeParams {
+    vertices: Option<Vec<Point>>,
+    x: Option<f32>,
+    y: Option<f32>,
+    radius: Option<f32>,
+}
+
+impl TryFrom<ShapeParams> for Shape {
+    type Error = &'static str;
+
+    fn try_from(params: ShapeParams) -> Result<Self, Self::Error> {
+        match params {
+            ShapeParams {
+                vertices: Some(vertices),
+                x: None,
+                y: None,
+                radius: None,
+            } => Ok(Self::Polygon { vertices }),
+            ShapeParams {
+                vertices: None,
+                x: Some(x),
+                y: Some(y),
+                radius: Some(radius),
+            } => Ok(Self::Circle { x, y, radius }),
+            _ => Err("expected either vertices or x, y and radius"),
+        }
+    }
+}
+
+impl From<Vec<Point>> for Shape {
+    fn from(vertices: Vec<Point>) -> Self {
+        Self::Polygon { vertices }
+    }
+}
+
+/// Takes the vertices out of a polygon, circles are handed back as the error
+impl TryFrom<Shape> for Vec<Point> {
+    type Error = Shape;
+
+    fn try_from(shape: Shape) -> Result<Self, Self::Error> {
+        match shape {
+            Shape::Polygon { vertices } => Ok(vertices),
+            circle => Err(circle),
+        }
+    }
+}
+
+/// Consecutive vertex pairs of a closed polygon, wrapping the last to the first
+fn closed_pairs(vertices: &[Point]) -> impl Iterator<Item = (&Point, &Point)> {
+    let wrapped = vertices.iter().skip(1).chain(vertices.first());
+    vertices.iter().zip(wrapped)
+}
+
+/// Shoelace area, negative for polygons wound counter-clockwise on screen
+fn signed_area(vertices: &[Point]) -> f32 {
+    closed_pairs(vertices).map(|(a, b)| a.cross(b)).sum::<f32>() / 2.0
+}
+
+/// See [`Shape::centroid`]
+fn polygon_centroid(vertices: &[Point]) -> Point {
+    let area = signed_area(vertices);
+    if area == 0.0 {
+        if vertices.is_empty() {
+            return Point::default();
+        }
+        let sum = vertices.iter().fold(Point::default(), |sum, v| sum + *v);
+        return sum / vertices.len() as f32;
+    }
+    let sum =
+        closed_pairs(vertices).fold(Point::default(), |sum, (a, b)| sum + (*a + *b) * a.cross(b));
+    sum / (6.0 * area)
+}
+
+/// See [`Shape::contains_point`]
+fn polygon_contains(vertices: &[Point], p: &Point) -> bool {
+    let mut inside = false;
+    for (a, b) in closed_pairs(vertices) {
+        if on_segment(p, a, b) {
+            return true;
+        }
+        if (a.y > p.y) != (b.y > p.y) && p.x < a.x + (p.y - a.y) * (b.x - a.x) / (b.y - a.y) {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+/// Smallest box containing every point, `None` if there are none
+fn polygon_bounds(vertices: &[Point]) -> Option<(Point, Point)> {
+    let first = vertices.first()?;
+    Some(
+        vertices
+            .iter()
+            .fold((*first, *first), |bounds, v| union_bounds(bounds, (*v, *v))),
+    )
+}
+
+fn union_bounds((a_min, a_max): (Point, Point), (b_min, b_max): (Point, Point)) -> (Point, Point) {
+    (
+        Point::new(a_min.x.min(b_min.x), a_min.y.min(b_min.y)),
+        Point::new(a_max.x.max(b_max.x), a_max.y.max(b_max.y)),
+    )
+}
+
+/// Applies a reflection to every vertex, then restores the winding it flipped
+fn mirror_vertices(vertices: &mut [Point], reflect: impl FnMut(&mut Point)) {
+    vertices.iter_mut().for_each(reflect);
+    vertices.reverse();
+}
+
+/// Whether `p` lies on the segment from `a` to `b`, within `1e-5`. A zero
+/// length segment is just the point `a`.
+fn on_segment(p: &Point, a: &Point, b: &Point) -> bool {
+    const EPSILON: f32 = 1e-5;
+
+    let ab = *b - *a;
+    let ap = *p - *a;
+    let length_squared = ab.length_squared();
+    if length_squared == 0.0 {
+        return ap.length_squared() <= EPSILON * EPSILON;
+    }
+    // |cross| / |ab| is the distance from `p` to the line through `a` and `b`
+    let cross = ab.cross(&ap);
+    cross * cross <= EPSILON * EPSILON * length_squared
+        && (0.0..=length_squared).contains(&ap.dot(&ab))
+}
+
+/// Whether segment `a`-`b` touches or crosses segment `c`-`d`
+fn segments_intersect(a: &Point, b: &Point, c: &Point, d: &Point) -> bool {
+    let ab = *b - *a;
+    let cd = *d - *c;
+    let (d1, d2) = (ab.cross(&(*c - *a)), ab.cross(&(*d - *a)));
+    let (d3, d4) = (cd.cross(&(*a - *c)), cd.cross(&(*b - *c)));
+    if d1 * d2 < 0.0 && d3 * d4 < 0.0 {
+        return true;
+    }
+    on_segment(c, a, b) || on_segment(d, a, b) || on_segment(a, c, d) || on_segment(b, c, d)
+}
+
+impl Shape {
+    /// Axis aligned rectangle centered on `center`
+    pub fn rectangle(center: Point, width: f32, height: f32) -> Self {
+        let half = Point::new(width / 2.0, height / 2.0);
+        Self::rectangle_corners(center - half, center + half)
+    }
+    /// Axis aligned rectangle spanning `min` to `max`, wound counter-clockwise
+    /// like the editor's rectangles
+    pub fn rectangle_corners(min: Point, max: Point) -> Self {
+        Self::Polygon {
+            vertices: vec![min, Point::new(min.x, max.y), max, Point::new(max.x, min.y)],
+        }
+    }
+    /// Regular polygon with `sides` vertices at `radius` from `center`, wound
+    /// counter-clockwise starting from the +x axis
+    ///
+    /// # Panics
+    ///
+    /// Panics if `sides` is less than 3.
+    #[cfg(feature = "std")]
+    pub fn regular_polygon(center: Point, radius: f32, sides: u32) -> Self {
+        assert!(
+            sides >= 3,
+            "a polygon needs at least 3 sides, got {}",
+            sides
+        );
+        let step = -core::f32::consts::TAU / sides as f32;
+        Self::Polygon {
+            vertices: (0..sides)
+                .map(|i| center + Point::new(radius, 0.0).rotate(step * i as f32))
+                .collect(),
+        }
+    }
+    /// Area of the shape, always positive regardless of winding
+    pub fn area(&self) -> f32 {
+        match self {
+            Self::Polygon { vertices } => signed_area(vertices).abs(),
+            Self::Circle { radius, .. } => core::f32::consts::PI * radius * radius,
+        }
+    }
+    /// Geometric center of the shape
+    ///
+    /// Polygons use the area weighted centroid. Zero area polygons fall back to
+    /// the average of their vertices, and an empty polygon gives the origin.
+    pub fn centroid(&self) -> Point {
+        match self {
+            Self::Polygon { vertices } => polygon_centroid(vertices),
+            Self::Circle { x, y, .. } => Point::new(*x, *y),
+        }
+    }
+    /// Axis aligned bounding box as `(min, max)`, an empty polygon gives a zero
+    /// sized box at the origin
+    pub fn bounding_box(&self) -> (Point, Point) {
+        match self {
+            Self::Polygon { vertices } => {
+                polygon_bounds(vertices).unwrap_or((Point::default(), Point::default()))
+            }
+            Self::Circle { x, y, radius } => (
+                Point::new(x - radius, y - radius),
+                Point::new(x + radius, y + radius),
+            ),
+        }
+    }
+    /// Whether `p` is inside the shape, using the even-odd rule for polygons.
+    /// Points on an edge or on a circle's boundary count as inside.
+    pub fn contains_point(&self, p: &Point) -> bool {
+        match self {
+            Self::Polygon { vertices } => polygon_contains(vertices, p),
+            Self::Circle { x, y, radius } => {
+                p.distance_squared(&Point::new(*x, *y)) <= radius * radius
+            }
+        }
+    }
+    /// Moves the shape by `delta`
+    pub fn translate(&mut self, delta: Point) {
+        match self {
+            Self::Polygon { vertices } => vertices.iter_mut().for_each(|v| *v += delta),
+            Self::Circle { x, y, .. } => {
+                *x += delta.x;
+                *y += delta.y;
+            }
+        }
+    }
+    /// Scales the shape by `factor` about `about`, circles scale their radius too
+    pub fn scale(&mut self, factor: f32, about: Point) {
+        match self {
+            Self::Polygon { vertices } => vertices
+                .iter_mut()
+                .for_each(|v| *v = about + (*v - about) * factor),
+            Self::Circle { x, y, radius } => {
+                let center = about + (Point::new(*x, *y) - about) * factor;
+                *x = center.x;
+                *y = center.y;
+                *radius *= factor.abs();
+            }
+        }
+    }
+    /// Rotates the shape by `radians` about `about`, see [`Point::rotate`].
+    /// Circles keep their radius.
+    #[cfg(feature = "std")]
+    pub fn rotate(&mut self, radians: f32, about: Point) {
+        match self {
+            Self::Polygon { vertices } => vertices
+                .iter_mut()
+                .for_each(|v| *v = v.rotate_around(&about, radians)),
+            Self::Circle { x, y, .. } => {
+                let center = Point::new(*x, *y).rotate_around(&about, radians);
+                *x = center.x;
+                *y = center.y;
+            }
+        }
+    }
+    /// Reflects the shape about the vertical line `x = axis_x`. Polygon vertices
+    /// are reversed afterwards so the winding order is kept.
+    pub fn mirror_horizontal(&mut self, axis_x: f32) {
+        match self {
+            Self::Polygon { vertices } => mirror_vertices(vertices, |v| v.x = 2.0 * axis_x - v.x),
+            Self::Circle { x, .. } => *x = 2.0 * axis_x - *x,
+        }
+    }
+    /// Reflects the shape about the horizontal line `y = axis_y`. Polygon vertices
+    /// are reversed afterwards so the winding order is kept.
+    pub fn mirror_vertical(&mut self, axis_y: f32) {
+        match self {
+            Self::Polygon { vertices } => mirror_vertices(vertices, |v| v.y = 2.0 * axis_y - v.y),
+            Self::Circle { y, .. } => *y = 2.0 * axis_y - *y,
+        }
+    }
+    /// Polygon approximation of the shape. Circles become a regular polygon with
+    /// `segments` vertices on the circle, polygons are cloned as-is.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `segments` is less than 3 for a circle.
+    #[cfg(feature = "std")]
+    pub fn to_polygon(&self, segments: u32) -> Shape {
+        match self {
+            Self::Polygon { .. } => self.clone(),
+            Self::Circle { x, y, radius } => {
+                assert!(
+                    segments >= 3,
+                    "a polygon needs at least 3 segments, got {}",
+                    segments
+                );
+                Self::regular_polygon(Point::new(*x, *y), *radius, segments)
+            }
+        }
+    }
+    /// Whether a polygon is wound counter-clockwise on screen, `None` for circles.
+    /// Polygons with no area are not counter-clockwise.
+    pub fn is_ccw(&self) -> Option<bool> {
+        match self {
+            Self::Polygon { vertices } => Some(signed_area(vertices) < 0.0),
+            Self::Circle { .. } => None,
+        }
+    }
+    /// Reverses a clockwise polygon so it's wound counter-clockwise like the
+    /// editor's shapes, circles are left alone
+    pub fn ensure_ccw(&mut self) {
+        if matches!(self, Self::Polygon { vertices } if signed_area(vertices) > 0.0) {
+            self.reverse_winding();
+        }
+    }
+    /// Reverses the vertex order of a polygon, flipping its winding. Circles
+    /// are left alone.
+    pub fn reverse_winding(&mut self) {
+        if let Self::Polygon { vertices } = self {
+            vertices.reverse();
+        }
+    }
+    /// Whether the shape doesn't cross itself. Polygons are checked by testing
+    /// every pair of non-adjacent edges, skipping repeated consecutive vertices.
+    /// Circles are always simple.
+    pub fn is_simple(&self) -> bool {
+        let Self::Polygon { vertices } = self else {
+            return true;
+        };
+        // A repeated vertex is a zero length edge touching the edges either side
+        // of it, which aren't adjacent to each other
+        let mut vertices = vertices.clone();
+        vertices.dedup();
+        while vertices.len() > 1 && vertices.first() == vertices.last() {
+            vertices.pop();
+        }
+        let n = vertices.len();
+        let edges: Vec<_> = closed_pairs(&vertices).collect();
+        for i in 0..n {
+            for j in i + 2..n {
+                // The first and last edges share a vertex
+                if i == 0 && j == n - 1 {
+                    continue;
+                }
+                let ((a, b), (c, d)) = (edges[i], edges[j]);
+                if segments_intersect(a, b, c, d) {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+    /// Whether a polygon is convex, `None` for circles. Collinear vertices are
+    /// allowed, but polygons with fewer than three vertices or that cross
+    /// themselves aren't convex.
+    pub fn is_convex(&self) -> Option<bool> {
+        let Self::Polygon { vertices } = self else {
+            return None;
+        };
+        if vertices.len() < 3 {
+            return Some(false);
+        }
+        let mut sign = 0.0;
+        for (i, (a, b)) in closed_pairs(vertices).enumerate() {
+            let c = &vertices[(i + 2) % vertices.len()];
+            let turn = (*b - *a).cross(&(*c - *b));
+            if turn == 0.0 {
+                continue;
+            }
+            if sign != 0.0 && turn.signum() != sign {
+                return Some(false);
+            }
+            sign = turn.signum();
+        }
+        Some(sign != 0.0 && self.is_simple())
+    }
+    /// Whether the shapes match within `epsilon`, comparing polygons vertex by
+    /// vertex in order and circles by center and radius
+    pub fn approx_eq(&self, other: &Shape, epsilon: f32) -> bool {
+        match (self, other) {
+            (Self::Polygon { vertices: a }, Self::Polygon { vertices: b }) => {
+                a.len() == b.len() && a.iter().zip(b).all(|(a, b)| a.approx_eq(b, epsilon))
+            }
+            (
+                Self::Circle { x, y, radius },
+                Self::Circle {
+                    x: other_x,
+                    y: other_y,
+                    radius: other_radius,
+                },
+            ) => {
+                Point::new(*x, *y).approx_eq(&Point::new(*other_x, *other_y), epsilon)
+                    && (radius - other_radius).abs() <= epsilon
+            }
+            _ => false,
+        }
+    }
+    /// Interpolates vertex by vertex towards `other`, see [`Point::lerp`]. Gives
+    /// `None` unless both are circles or polygons with the same vertex count.
+    pub fn lerp(&self, other: &Shape, t: f32) -> Option<Shape> {
+        match (self, other) {
+            (Self::Polygon { vertices: a }, Self::Polygon { vertices: b })
+                if a.len() == b.len() =>
+            {
+                Some(Self::Polygon {
+                    vertices: a.iter().zip(b).map(|(a, b)| a.lerp(b, t)).collect(),
+                })
+            }
+            (
+                Self::Circle { x, y, radius },
+                Self::Circle {
+                    x: other_x,
+                    y: other_y,
+                    radius: other_radius,
+                },
+            ) => {
+                let center = Point::new(*x, *y).lerp(&Point::new(*other_x, *other_y), t);
+                Some(Self::Circle {
+                    x: center.x,
+                    y: center.y,
+                    radius: radius + (other_radius - radius) * t,
+                })
+            }
+            _ => None,
+        }
+    }
+    /// Row of `count` triangular spikes, each `width` wide and `height` tall,
+    /// with the flat base running right from `start` and the peaks pointing up
+    /// on screen. The polygon has `2 * count + 1` vertices.
+    ///
+    /// The valleys between spikes sit `height / 10` above the base, leaving a
+    /// thin band that joins the spikes so the outline never touches itself.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `count` is 0.
+    pub fn spike_strip(start: Point, count: u32, width: f32, height: f32) -> Self {
+        assert!(count > 0, "a spike strip needs at least 1 spike");
+        let mut vertices = vec![start, start + Point::new(count as f32 * width, 0.0)];
+        for i in (0..count).rev() {
+            let left = start.x + i as f32 * width;
+            vertices.push(Point::new(left + width / 2.0, start.y - height));
+            if i > 0 {
+                vertices.push(Point::new(left, start.y - height / 10.0));
+            }
+        }
+        Self::Polygon { vertices }
+    }
+    /// Consecutive vertex pairs of a polygon, closing back from the last vertex
+    /// to the first. Circles have no edges and give an empty list, use
+    /// [`Shape::to_polygon`] first to get an approximation.
+    pub fn edges(&self) -> Vec<(Point, Point)> {
+        match self {
+            Self::Polygon { vertices } => closed_pairs(vertices).map(|(a, b)| (*a, *b)).collect(),
+            Self::Circle { .. } => vec![],
+        }
+    }
+    /// Total edge length, or `2πr` for circles
+    #[cfg(feature = "std")]
+    pub fn perimeter(&self) -> f32 {
+        match self {
+            Self::Polygon { vertices } => closed_pairs(vertices).map(|(a, b)| a.distance(b)).sum(),
+            Self::Circle { radius, .. } => core::f32::consts::TAU * radius,
+        }
+    }
+    /// Removes consecutive polygon vertices within `1e-5` of each other,
+    /// including a last vertex repeating the first. Circles are left alone.
+    pub fn dedup_vertices(&mut self) {
+        if let Self::Polygon { vertices } = self {
+            vertices.dedup_by(|b, a| a.approx_eq(b, 1e-5));
+            while vertices.len() > 1 && vertices[0].approx_eq(&vertices[vertices.len() - 1], 1e-5) {
+                vertices.pop();
+            }
+        }
+    }
+    /// Moves every edge of a polygon `distance` along its outward normal, so
+    /// positive values grow the shape and negative ones shrink it. Circles
+    /// change radius instead, stopping at zero. Shrinking a polygon far enough
+    /// that any edge would turn around collapses every vertex onto its
+    /// centroid rather than turning it inside out. Only convex polygons give
+    /// reliable results, concave corners can fold over.
+    #[cfg(feature = "std")]
+    pub fn offset(&self, distance: f32) -> Shape {
+        match self {
+            Self::Polygon { vertices } => {
+                // outward normals depend on which way the polygon winds
+                let sign = if signed_area(vertices) > 0.0 {
+                    1.0
+                } else {
+                    -1.0
+                };
+                let normals: Vec<Point> = closed_pairs(vertices)
+                    .map(|(a, b)| {
+                        let d = *b - *a;
+                        Point::new(d.y, -d.x).normalize() * sign
+                    })
+                    .collect();
+                let n = vertices.len();
+                let offset: Vec<Point> = (0..n)
+                    .map(|i| {
+                        let (before, after) = (normals[(i + n - 1) % n], normals[i]);
+                        let denominator = 1.0 + before.dot(&after);
+                        if denominator.abs() < 1e-6 {
+                            vertices[i] + after * distance
+                        } else {
+                            vertices[i] + (before + after) * (distance / denominator)
+                        }
+                    })
+                    .collect();
+                // shrinking too far reverses edges instead of stopping
+                let reversed = closed_pairs(&offset)
+                    .zip(closed_pairs(vertices))
+                    .any(|((a, b), (c, d))| (*b - *a).dot(&(*d - *c)) < 0.0);
+                if distance < 0.0 && reversed {
+                    return Self::Polygon {
+                        vertices: vec![polygon_centroid(vertices); n],
+                    };
+                }
+                Self::Polygon { vertices: offset }
+            }
+            Self::Circle { x, y, radius } => Self::Circle {
+                x: *x,
+                y: *y,
+                radius: (radius + distance).max(0.0),
+            },
+        }
+    }
+    /// Whether the segment `a`-`b` crosses the outline of the shape or lies
+    /// inside it. Touching an edge or the circle counts.
+    pub fn intersects_segment(&self, a: &Point, b: &Point) -> bool {
+        match self {
+            Self::Polygon { vertices } => {
+                polygon_contains(vertices, a)
+                    || closed_pairs(vertices).any(|(c, d)| segments_intersect(a, b, c, d))
+            }
+            Self::Circle { x, y, radius } => {
+                let center = Point::new(*x, *y);
+                let ab = *b - *a;
+                let t = if ab.length_squared() > 0.0 {
+                    ((center - *a).dot(&ab) / ab.length_squared()).clamp(0.0, 1.0)
+                } else {
+                    0.0
+                };
+                a.lerp(b, t).distance_squared(&center) <= radius * radius
+            }
+        }
+    }
+}
+
+/// Convex hull of `points` using Andrew's monotone chain, wound counter-clockwise
+/// on screen. Interior and collinear points are dropped, so fewer than three
+/// distinct or non-collinear points give a polygon of just the extreme points.
+pub fn convex_hull(points: &[Point]) -> Shape {
+    let mut sorted = points.to_vec();
+    sorted.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+    sorted.dedup();
+    if sorted.len() < 3 {
+        return Shape::Polygon { vertices: sorted };
+    }
+
+    // Builds one half of the hull, only keeping clockwise turns on screen
+    let half = |points: &mut dyn Iterator<Item = &Point>| {
+        let mut chain: Vec<Point> = vec![];
+        for p in points {
+            while let [.., a, b] = chain[..] {
+                if (b - a).cross(&(*p - a)) > 0.0 {
+                    break;
+                }
+                chain.pop();
+            }
+            chain.push(*p);
+        }
+        chain.pop();
+        chain
+    };
+    let mut vertices = half(&mut sorted.iter());
+    vertices.extend(half(&mut sorted.iter().rev()));
+    vertices.reverse();
+    Shape::Polygon { vertices }
+}
+
+/// Square tile covering grid cell `(col, row)`, spanning `col * size` to
+/// `(col + 1) * size` horizontally and likewise for rows
+pub fn grid_tile(col: i32, row: i32, size: f32) -> Shape {
+    let min = Point::new(col as f32, row as f32) * size;
+    Shape::rectangle_corners(min, min + Point::ONE * size)
+}
+
+/// Static tiles for each cell, built with a material constructor such as
+/// [`Entity::new_normal`]
+pub fn grid_tiles(
+    cells: &[(i32, i32)],
+    size: f32,
+    material: fn(Shape, bool) -> Entity,
+) -> Vec<Entity> {
+    cells
+        .iter()
+        .map(|&(col, row)| material(grid_tile(col, row, size), true))
+        .collect()
+}
+
+/// `count` copies of `entity`, the `i`th moved by `step * i` so the first stays
+/// in place. Handy for staircases and fences.
+pub fn repeat_along(entity: &Entity, step: Point, count: u32) -> Vec<Entity> {
+    (0..count)
+        .map(|i| {
+            let mut copy = entity.clone();
+            copy.translate(step * i as f32);
+            copy
+        })
+        .collect()
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Default, Clone, Copy, Debug)]
 #[serde(rename_all = "camelCase")]
 pub enum TextAlign {
+    #[default]
     Left,
     Center,
     Right,
     Justify,
 }
 
+/// Width and height of the square centered on a `Player`, `Door`, `Text`,
+/// `Boost` or `Pickup` used when hit testing them
+pub const MARKER_SIZE: f32 = 60.0;
+
 macro_rules! define_entities {
-    ( $( $material: ident),* ) => {
-        #[derive(Serialize, Clone)]
+    ( $( $material: ident : $name: literal => $constructor: ident ),* ) => {
+        /// Params this crate doesn't model are kept in each variant's `extra`, so
+        /// they survive a load and save
+        #[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
         #[serde(rename_all = "camelCase", tag = "type", content = "params")]
         pub enum Entity {
             #[serde(rename_all = "camelCase")]
@@ -116,6 +1084,10 @@ macro_rules! define_entities {
                 y: f32,
                 #[serde(flatten)]
                 ammo: Ammo,
+                #[serde(flatten)]
+                extra: serde_json::Map<String, serde_json::Value>,
+                #[serde(skip)]
+                note: Option<String>,
             },
             #[serde(rename_all = "camelCase", rename = "endpoint")]
             Door {
@@ -124,6 +1096,51 @@ macro_rules! define_entities {
                 x: f32,
                 y: f32,
                 right_facing: bool,
+                #[serde(flatten)]
+                extra: serde_json::Map<String, serde_json::Value>,
+                #[serde(skip)]
+                note: Option<String>,
+            },
+            /// Boost pad that launches the player along `angle`. The editor's
+            /// field names aren't documented, so this follows `Door` and adds a
+            /// `strength` multiplier.
+            #[serde(rename_all = "camelCase")]
+            Boost {
+                is_static: bool,
+                angle: i32,
+                x: f32,
+                y: f32,
+                strength: f32,
+                #[serde(flatten)]
+                extra: serde_json::Map<String, serde_json::Value>,
+                #[serde(skip)]
+                note: Option<String>,
+            },
+            /// Ammo lying in the world, separate from the player's magazine.
+            /// Serialized as `{"type": "pickup", "params": {"x", "y", "ammoType"}}`
+            /// with the same ammo names as a magazine.
+            #[serde(rename_all = "camelCase")]
+            Pickup {
+                x: f32,
+                y: f32,
+                ammo_type: AmmoType,
+                #[serde(flatten)]
+                extra: serde_json::Map<String, serde_json::Value>,
+                #[serde(skip)]
+                note: Option<String>,
+            },
+            /// Platform that travels through `waypoints` at `speed`. Built with
+            /// [`Entity::new_moving_platform`], which needs at least two waypoints.
+            #[serde(rename_all = "camelCase")]
+            MovingPlatform {
+                #[serde(flatten)]
+                shape: Shape,
+                waypoints: Vec<Point>,
+                speed: f32,
+                #[serde(flatten)]
+                extra: serde_json::Map<String, serde_json::Value>,
+                #[serde(skip)]
+                note: Option<String>,
             },
             #[serde(rename_all = "camelCase")]
             Text {
@@ -131,32 +1148,178 @@ macro_rules! define_entities {
                 x: f32,
                 y: f32,
                 // TODO maybe use &str and deal with lifetimes
+                // keyed by language, sorted so the output is stable
                 #[serde(rename = "copy")]
-                text: HashMap<String, String>,
+                text: BTreeMap<String, String>,
                 anchor: Point,
                 align: TextAlign,
-                fill_color: i32,
+                fill_color: Color,
                 opacity: f32,
+                #[serde(flatten)]
+                extra: serde_json::Map<String, serde_json::Value>,
+                #[serde(skip)]
+                note: Option<String>,
             },
             #[serde(rename_all = "camelCase")]
             Paint {
-                fill_color: i32,
+                fill_color: Color,
                 opacity: f32,
                 vertices: Vec<Point>,
+                #[serde(flatten)]
+                extra: serde_json::Map<String, serde_json::Value>,
+                #[serde(skip)]
+                note: Option<String>,
             },
             $(
-            #[serde(rename_all = "camelCase")]
+            #[serde(rename_all = "camelCase", rename = $name)]
             $material {
                 is_static: bool,
                 #[serde(flatten)]
                 shape: Shape,
+                #[serde(flatten)]
+                extra: serde_json::Map<String, serde_json::Value>,
+                #[serde(skip)]
+                note: Option<String>,
             },
             )*
         }
+
+        impl Entity {
+            $(
+            #[doc = concat!("Shorthand for building [`Entity::", stringify!($material), "`]")]
+            pub fn $constructor(shape: Shape, is_static: bool) -> Self {
+                Self::$material {
+                    is_static,
+                    shape,
+                    extra: serde_json::Map::new(),
+                    note: None,
+                }
+            }
+            )*
+
+            /// Shape of a material entity or moving platform
+            fn shape(&self) -> Option<&Shape> {
+                match self {
+                    $( Self::$material { shape, .. } )|* => Some(shape),
+                    Self::MovingPlatform { shape, .. } => Some(shape),
+                    _ => None,
+                }
+            }
+
+            fn shape_mut(&mut self) -> Option<&mut Shape> {
+                match self {
+                    $( Self::$material { shape, .. } )|* => Some(shape),
+                    Self::MovingPlatform { shape, .. } => Some(shape),
+                    _ => None,
+                }
+            }
+
+            /// Params this crate doesn't model, kept so they're written back out
+            pub fn extra(&self) -> &serde_json::Map<String, serde_json::Value> {
+                match self {
+                    Self::Player { extra, .. }
+                    | Self::Door { extra, .. }
+                    | Self::Boost { extra, .. }
+                    | Self::Pickup { extra, .. }
+                    | Self::MovingPlatform { extra, .. }
+                    | Self::Text { extra, .. }
+                    | Self::Paint { extra, .. } => extra,
+                    $( Self::$material { extra, .. } )|* => extra,
+                }
+            }
+
+            /// Mutable access to [`Entity::extra`]
+            pub fn extra_mut(&mut self) -> &mut serde_json::Map<String, serde_json::Value> {
+                match self {
+                    Self::Player { extra, .. }
+                    | Self::Door { extra, .. }
+                    | Self::Boost { extra, .. }
+                    | Self::Pickup { extra, .. }
+                    | Self::MovingPlatform { extra, .. }
+                    | Self::Text { extra, .. }
+                    | Self::Paint { extra, .. } => extra,
+                    $( Self::$material { extra, .. } )|* => extra,
+                }
+            }
+
+            /// Developer note attached while authoring. Notes are kept by clones
+            /// but never serialized, so they don't reach the exported level.
+            pub fn note(&self) -> Option<&str> {
+                match self {
+                    Self::Player { note, .. }
+                    | Self::Door { note, .. }
+                    | Self::Boost { note, .. }
+                    | Self::Pickup { note, .. }
+                    | Self::MovingPlatform { note, .. }
+                    | Self::Text { note, .. }
+                    | Self::Paint { note, .. } => note.as_deref(),
+                    $( Self::$material { note, .. } )|* => note.as_deref(),
+                }
+            }
+
+            /// Sets or clears the note, see [`Entity::note`]
+            pub fn set_note(&mut self, value: Option<String>) {
+                match self {
+                    Self::Player { note, .. }
+                    | Self::Door { note, .. }
+                    | Self::Boost { note, .. }
+                    | Self::Pickup { note, .. }
+                    | Self::MovingPlatform { note, .. }
+                    | Self::Text { note, .. }
+                    | Self::Paint { note, .. } => *note = value,
+                    $( Self::$material { note, .. } )|* => *note = value,
+                }
+            }
+
+            /// Name used for the entity's `type` tag when serialized
+            pub fn type_name(&self) -> &'static str {
+                match self {
+                    Self::Player { .. } => "player",
+                    Self::Door { .. } => "endpoint",
+                    Self::Boost { .. } => "boost",
+                    Self::Pickup { .. } => "pickup",
+                    Self::MovingPlatform { .. } => "movingPlatform",
+                    Self::Text { .. } => "text",
+                    Self::Paint { .. } => "paint",
+                    $( Self::$material { .. } => $name, )*
+                }
+            }
+
+            /// Moves the entity by `delta`
+            pub fn translate(&mut self, delta: Point) {
+                match self {
+                    Self::Player { x, y, .. }
+                    | Self::Door { x, y, .. }
+                    | Self::Boost { x, y, .. }
+                    | Self::Pickup { x, y, .. }
+                    | Self::Text { x, y, .. } => {
+                        *x += delta.x;
+                        *y += delta.y;
+                    }
+                    Self::Paint { vertices, .. } => vertices.iter_mut().for_each(|v| *v += delta),
+                    Self::MovingPlatform { shape, waypoints, .. } => {
+                        shape.translate(delta);
+                        waypoints.iter_mut().for_each(|v| *v += delta);
+                    }
+                    $( Self::$material { shape, .. } )|* => shape.translate(delta),
+                }
+            }
+        }
     };
 }
 
-define_entities!(Normal, Ice, Breakable, Deadly, Bouncy);
+define_entities!(
+    Normal: "normal" => new_normal,
+    Ice: "ice" => new_ice,
+    Breakable: "breakable" => new_breakable,
+    Deadly: "deadly" => new_deadly,
+    Bouncy: "bouncy" => new_bouncy
+);
+
+/// Wraps an angle in degrees into `0..360`
+fn normalize_angle(degrees: i32) -> i32 {
+    degrees.rem_euclid(360)
+}
 
 impl Entity {
     pub fn new_text(pos: Point, text: &str) -> Self {
@@ -164,143 +1327,3990 @@ impl Entity {
             angle: 0,
             x: pos.x,
             y: pos.y,
-            text: HashMap::from([(String::from("en"), text.to_string())]),
+            text: BTreeMap::from([(String::from("en"), text.to_string())]),
             anchor: Point::new(0.5, 0.5),
-            align: TextAlign::Left,
-            fill_color: 16777215,
+            align: TextAlign::default(),
+            fill_color: Color::WHITE,
             opacity: 1.0,
+            extra: serde_json::Map::new(),
+            note: None,
         }
     }
-}
-
-#[derive(Serialize)]
-#[serde(rename_all = "camelCase")]
-pub struct Level {
-    pub name: String,
-    pub timings: [i32; 2],
+    /// Creates a `Text` like [`Entity::new_text`] but with every translation
+    /// given up front, failing if there are none since the editor needs one.
+    /// Takes a `HashMap` or `BTreeMap` of language to text.
+    pub fn new_text_multi<I: IntoIterator<Item = (String, String)>>(
+        pos: Point,
+        translations: I,
+    ) -> Result<Self, NoTranslationsError> {
+        let translations: BTreeMap<_, _> = translations.into_iter().collect();
+        if translations.is_empty() {
+            return Err(NoTranslationsError);
+        }
+        let mut text = Self::new_text(pos, "");
+        if let Self::Text { text, .. } = &mut text {
+            *text = translations;
+        }
+        Ok(text)
+    }
+    /// Creates a static [`Entity::Boost`] at `pos` launching along `angle` degrees
+    pub fn new_boost(pos: Point, angle: i32, strength: f32) -> Self {
+        Self::Boost {
+            is_static: true,
+            angle,
+            x: pos.x,
+            y: pos.y,
+            strength,
+            extra: serde_json::Map::new(),
+            note: None,
+        }
+    }
+    /// Creates an [`Entity::Pickup`] of `ammo_type` at `pos`
+    pub fn new_pickup(pos: Point, ammo_type: AmmoType) -> Self {
+        Self::Pickup {
+            x: pos.x,
+            y: pos.y,
+            ammo_type,
+            extra: serde_json::Map::new(),
+            note: None,
+        }
+    }
+    /// Creates an [`Entity::MovingPlatform`], failing if it has fewer than two
+    /// waypoints to travel between
+    pub fn new_moving_platform(
+        shape: Shape,
+        waypoints: Vec<Point>,
+        speed: f32,
+    ) -> Result<Self, TooFewWaypointsError> {
+        if waypoints.len() < 2 {
+            return Err(TooFewWaypointsError {
+                count: waypoints.len(),
+            });
+        }
+        Ok(Self::MovingPlatform {
+            shape,
+            waypoints,
+            speed,
+            extra: serde_json::Map::new(),
+            note: None,
+        })
+    }
+    /// Creates an opaque white [`Entity::Paint`]
+    pub fn new_paint(vertices: Vec<Point>) -> Self {
+        Self::Paint {
+            fill_color: Color::WHITE,
+            opacity: 1.0,
+            vertices,
+            extra: serde_json::Map::new(),
+            note: None,
+        }
+    }
+    /// Sets the fill color of a `Text` or `Paint`, other entities are returned unchanged.
+    /// Packed integers have the bits above the 24-bit RGB range masked off.
+    pub fn with_color(mut self, color: impl Into<Color>) -> Self {
+        if let Self::Text { fill_color, .. } | Self::Paint { fill_color, .. } = &mut self {
+            *fill_color = color.into();
+        }
+        self
+    }
+    /// The fill color of a `Text` or `Paint`, `None` for other entities
+    pub fn fill_color(&self) -> Option<Color> {
+        match self {
+            Self::Text { fill_color, .. } | Self::Paint { fill_color, .. } => Some(*fill_color),
+            _ => None,
+        }
+    }
+    /// Sets the opacity of a `Text` or `Paint`, other entities are returned unchanged.
+    /// The value is clamped into `0.0..=1.0`; a NaN is kept as is and reported by
+    /// [`Level::validate`].
+    pub fn with_opacity(mut self, value: f32) -> Self {
+        if let Self::Text { opacity, .. } | Self::Paint { opacity, .. } = &mut self {
+            *opacity = value.clamp(0.0, 1.0);
+        }
+        self
+    }
+    /// Sets the alignment of a `Text`, other entities are returned unchanged
+    pub fn with_align(mut self, value: TextAlign) -> Self {
+        if let Self::Text { align, .. } = &mut self {
+            *align = value;
+        }
+        self
+    }
+    /// Sets the anchor of a `Text`, other entities are returned unchanged
+    pub fn with_anchor(mut self, value: Point) -> Self {
+        if let Self::Text { anchor, .. } = &mut self {
+            *anchor = value;
+        }
+        self
+    }
+    /// Adds or replaces the `lang` translation of a `Text`, other entities are
+    /// returned unchanged
+    pub fn with_translation(mut self, lang: &str, value: &str) -> Self {
+        let _ = self.add_translation(lang, value);
+        self
+    }
+    /// Adds or replaces the `lang` translation of a `Text`
+    pub fn add_translation(&mut self, lang: &str, value: &str) -> Result<(), WrongVariantError> {
+        match self {
+            Self::Text { text, .. } => {
+                text.insert(lang.to_string(), value.to_string());
+                Ok(())
+            }
+            _ => Err(WrongVariantError { expected: "text" }),
+        }
+    }
+    /// The editor's `locked` flag, `None` when the param is missing or isn't
+    /// a bool. The exact name isn't documented, so rather than a field it's
+    /// read from [`Entity::extra`] and only written out when set.
+    pub fn locked(&self) -> Option<bool> {
+        self.extra().get("locked")?.as_bool()
+    }
+    /// Sets the `locked` flag, `None` removes it
+    pub fn set_locked(&mut self, value: Option<bool>) {
+        match value {
+            Some(locked) => self
+                .extra_mut()
+                .insert(String::from("locked"), locked.into()),
+            None => self.extra_mut().remove("locked"),
+        };
+    }
+    /// Ammo of a `Player`, `None` for other entities
+    pub fn ammo(&self) -> Option<&Ammo> {
+        match self {
+            Self::Player { ammo, .. } => Some(ammo),
+            _ => None,
+        }
+    }
+    /// Replaces the ammo of a `Player`
+    pub fn set_ammo(&mut self, value: Ammo) -> Result<(), WrongVariantError> {
+        match self {
+            Self::Player { ammo, .. } => {
+                *ammo = value;
+                Ok(())
+            }
+            _ => Err(WrongVariantError { expected: "player" }),
+        }
+    }
+    /// Rotation in degrees of a `Player`, `Door`, `Text` or `Boost`. `Pickup`,
+    /// `MovingPlatform`, material and `Paint` entities have no angle and give `None`.
+    pub fn angle(&self) -> Option<i32> {
+        match self {
+            Self::Player { angle, .. }
+            | Self::Door { angle, .. }
+            | Self::Text { angle, .. }
+            | Self::Boost { angle, .. } => Some(*angle),
+            _ => None,
+        }
+    }
+    /// Sets the rotation in degrees, does nothing for `Pickup`, `MovingPlatform`,
+    /// material and `Paint` entities since they have no angle
+    pub fn set_angle(&mut self, degrees: i32) {
+        if let Self::Player { angle, .. }
+        | Self::Door { angle, .. }
+        | Self::Text { angle, .. }
+        | Self::Boost { angle, .. } = self
+        {
+            *angle = degrees;
+        }
+    }
+    /// [`Entity::angle`] in radians
+    pub fn angle_radians(&self) -> Option<f32> {
+        self.angle().map(|degrees| (degrees as f32).to_radians())
+    }
+    /// Sets the rotation from `radians`, rounded to the nearest whole degree
+    /// since that's all the editor stores
+    #[cfg(feature = "std")]
+    pub fn set_angle_radians(&mut self, radians: f32) {
+        self.set_angle(radians.to_degrees().round() as i32);
+    }
+    /// Rotates the entity by `radians` about `about`, see [`Point::rotate`].
+    ///
+    /// `Player`, `Door`, `Text` and `Boost` move their position and add the rotation to
+    /// their `angle`, rounded to whole degrees and wrapped into `0..360`. Both turn
+    /// clockwise on screen for positive values, so sprites stay lined up with
+    /// rotated geometry up to that rounding. A `Pickup` has no angle and only moves.
+    #[cfg(feature = "std")]
+    pub fn rotate(&mut self, radians: f32, about: Point) {
+        match self {
+            Self::Player { x, y, angle, .. }
+            | Self::Door { x, y, angle, .. }
+            | Self::Text { x, y, angle, .. }
+            | Self::Boost { x, y, angle, .. } => {
+                let p = Point::new(*x, *y).rotate_around(&about, radians);
+                *x = p.x;
+                *y = p.y;
+                let delta = radians.to_degrees().round() as i32;
+                *angle = normalize_angle(normalize_angle(*angle) + normalize_angle(delta));
+            }
+            Self::Pickup { x, y, .. } => {
+                let p = Point::new(*x, *y).rotate_around(&about, radians);
+                *x = p.x;
+                *y = p.y;
+            }
+            Self::Paint { vertices, .. } => vertices
+                .iter_mut()
+                .for_each(|v| *v = v.rotate_around(&about, radians)),
+            Self::MovingPlatform {
+                shape, waypoints, ..
+            } => {
+                shape.rotate(radians, about);
+                waypoints
+                    .iter_mut()
+                    .for_each(|v| *v = v.rotate_around(&about, radians));
+            }
+            _ => {
+                if let Some(shape) = self.shape_mut() {
+                    shape.rotate(radians, about);
+                }
+            }
+        }
+    }
+    /// Scales the entity by `factor` about `about`. Positions and geometry are
+    /// scaled, including circle radii, while angles are left alone.
+    pub fn scale(&mut self, factor: f32, about: Point) {
+        match self {
+            Self::Player { x, y, .. }
+            | Self::Door { x, y, .. }
+            | Self::Text { x, y, .. }
+            | Self::Boost { x, y, .. }
+            | Self::Pickup { x, y, .. } => {
+                let p = about + (Point::new(*x, *y) - about) * factor;
+                *x = p.x;
+                *y = p.y;
+            }
+            Self::Paint { vertices, .. } => vertices
+                .iter_mut()
+                .for_each(|v| *v = about + (*v - about) * factor),
+            Self::MovingPlatform {
+                shape, waypoints, ..
+            } => {
+                shape.scale(factor, about);
+                waypoints
+                    .iter_mut()
+                    .for_each(|v| *v = about + (*v - about) * factor);
+            }
+            _ => {
+                if let Some(shape) = self.shape_mut() {
+                    shape.scale(factor, about);
+                }
+            }
+        }
+    }
+    /// Reflects the entity about the vertical line `x = axis_x`. Doors turn to
+    /// face the other way and angles are negated, wrapped into `0..360`.
+    pub fn mirror_horizontal(&mut self, axis_x: f32) {
+        match self {
+            Self::Door {
+                x,
+                angle,
+                right_facing,
+                ..
+            } => {
+                *x = 2.0 * axis_x - *x;
+                *angle = normalize_angle(-normalize_angle(*angle));
+                *right_facing = !*right_facing;
+            }
+            Self::Player { x, angle, .. }
+            | Self::Text { x, angle, .. }
+            | Self::Boost { x, angle, .. } => {
+                *x = 2.0 * axis_x - *x;
+                *angle = normalize_angle(-normalize_angle(*angle));
+            }
+            Self::Pickup { x, .. } => *x = 2.0 * axis_x - *x,
+            Self::Paint { vertices, .. } => mirror_vertices(vertices, |v| v.x = 2.0 * axis_x - v.x),
+            Self::MovingPlatform {
+                shape, waypoints, ..
+            } => {
+                shape.mirror_horizontal(axis_x);
+                waypoints.iter_mut().for_each(|v| v.x = 2.0 * axis_x - v.x);
+            }
+            _ => {
+                if let Some(shape) = self.shape_mut() {
+                    shape.mirror_horizontal(axis_x);
+                }
+            }
+        }
+    }
+    /// Reflects the entity about the horizontal line `y = axis_y`. This is a
+    /// horizontal mirror followed by a half turn, so doors turn to face the other
+    /// way and angles become `180 - angle`, wrapped into `0..360`.
+    pub fn mirror_vertical(&mut self, axis_y: f32) {
+        match self {
+            Self::Door {
+                y,
+                angle,
+                right_facing,
+                ..
+            } => {
+                *y = 2.0 * axis_y - *y;
+                *angle = normalize_angle(180 - normalize_angle(*angle));
+                *right_facing = !*right_facing;
+            }
+            Self::Player { y, angle, .. }
+            | Self::Text { y, angle, .. }
+            | Self::Boost { y, angle, .. } => {
+                *y = 2.0 * axis_y - *y;
+                *angle = normalize_angle(180 - normalize_angle(*angle));
+            }
+            Self::Pickup { y, .. } => *y = 2.0 * axis_y - *y,
+            Self::Paint { vertices, .. } => mirror_vertices(vertices, |v| v.y = 2.0 * axis_y - v.y),
+            Self::MovingPlatform {
+                shape, waypoints, ..
+            } => {
+                shape.mirror_vertical(axis_y);
+                waypoints.iter_mut().for_each(|v| v.y = 2.0 * axis_y - v.y);
+            }
+            _ => {
+                if let Some(shape) = self.shape_mut() {
+                    shape.mirror_vertical(axis_y);
+                }
+            }
+        }
+    }
+    /// Position of the entity. `Player`, `Door`, `Text`, `Boost` and `Pickup`
+    /// report their `x` and `y`, while material, `Paint` and `MovingPlatform`
+    /// entities report the centroid of their geometry, or `None` if they have no
+    /// vertices. A `MovingPlatform` uses its shape and ignores its waypoints.
+    pub fn position(&self) -> Option<Point> {
+        match self {
+            Self::Player { x, y, .. }
+            | Self::Door { x, y, .. }
+            | Self::Text { x, y, .. }
+            | Self::Boost { x, y, .. }
+            | Self::Pickup { x, y, .. } => Some(Point::new(*x, *y)),
+            Self::Paint { vertices, .. } => {
+                (!vertices.is_empty()).then(|| polygon_centroid(vertices))
+            }
+            _ => match self.shape()? {
+                Shape::Polygon { vertices } if vertices.is_empty() => None,
+                shape => Some(shape.centroid()),
+            },
+        }
+    }
+    /// Bounding box as `(min, max)`. `Player`, `Door`, `Text`, `Boost` and
+    /// `Pickup` give a zero sized box at their position, and entities without vertices give `None`.
+    pub fn bounding_box(&self) -> Option<(Point, Point)> {
+        match self {
+            Self::Player { x, y, .. }
+            | Self::Door { x, y, .. }
+            | Self::Text { x, y, .. }
+            | Self::Boost { x, y, .. }
+            | Self::Pickup { x, y, .. } => Some((Point::new(*x, *y), Point::new(*x, *y))),
+            Self::Paint { vertices, .. } => polygon_bounds(vertices),
+            _ => match self.shape()? {
+                Shape::Polygon { vertices } => polygon_bounds(vertices),
+                shape => Some(shape.bounding_box()),
+            },
+        }
+    }
+    /// Whether `p` is inside the entity's shape. `Player`, `Door`, `Text`, `Boost`
+    /// and `Pickup` are hit tested against a [`MARKER_SIZE`] square centered on their position.
+    pub fn contains_point(&self, p: &Point) -> bool {
+        match self {
+            Self::Player { x, y, .. }
+            | Self::Door { x, y, .. }
+            | Self::Text { x, y, .. }
+            | Self::Boost { x, y, .. }
+            | Self::Pickup { x, y, .. } => {
+                let offset = *p - Point::new(*x, *y);
+                offset.x.abs() <= MARKER_SIZE / 2.0 && offset.y.abs() <= MARKER_SIZE / 2.0
+            }
+            Self::Paint { vertices, .. } => polygon_contains(vertices, p),
+            _ => self.shape().is_some_and(|shape| shape.contains_point(p)),
+        }
+    }
+    /// Moves the entity to `p`. Material, `Paint` and `MovingPlatform` entities
+    /// are translated so their centroid lands on `p`, waypoints included.
+    pub fn set_position(&mut self, p: Point) {
+        match self {
+            Self::Player { x, y, .. }
+            | Self::Door { x, y, .. }
+            | Self::Text { x, y, .. }
+            | Self::Boost { x, y, .. }
+            | Self::Pickup { x, y, .. } => {
+                *x = p.x;
+                *y = p.y;
+            }
+            Self::Paint { vertices, .. } => {
+                let delta = p - polygon_centroid(vertices);
+                vertices.iter_mut().for_each(|v| *v += delta);
+            }
+            _ => {
+                if let Some(centroid) = self.shape().map(Shape::centroid) {
+                    self.translate(p - centroid);
+                }
+            }
+        }
+    }
+    /// Points outlining the entity for wireframes. Polygons give their vertices,
+    /// circles are tessellated into 32 sides and positioned entities give the
+    /// corners of a [`MARKER_SIZE`] square centered on them.
+    #[cfg(feature = "std")]
+    pub fn outline(&self) -> Vec<Point> {
+        if let Self::Paint { vertices, .. } = self {
+            return vertices.clone();
+        }
+        let shape = match self.shape() {
+            Some(shape) => shape.to_polygon(32),
+            None => match self.position() {
+                Some(p) => Shape::rectangle(p, MARKER_SIZE, MARKER_SIZE),
+                None => return vec![],
+            },
+        };
+        Vec::try_from(shape).expect("only polygons are left")
+    }
+    /// Whether both entities have the same type and geometry within `epsilon`.
+    /// Positioned entities compare positions, `Paint` compares vertices in order
+    /// and shaped entities use [`Shape::approx_eq`], with moving platforms also
+    /// comparing waypoints. Other params such as text or ammo are ignored.
+    pub fn approx_eq(&self, other: &Entity, epsilon: f32) -> bool {
+        if self.type_name() != other.type_name() {
+            return false;
+        }
+        let points_eq = |a: &[Point], b: &[Point]| {
+            a.len() == b.len() && a.iter().zip(b).all(|(a, b)| a.approx_eq(b, epsilon))
+        };
+        match (self, other) {
+            (Self::Paint { vertices: a, .. }, Self::Paint { vertices: b, .. }) => points_eq(a, b),
+            (
+                Self::MovingPlatform {
+                    shape: a,
+                    waypoints: a_waypoints,
+                    ..
+                },
+                Self::MovingPlatform {
+                    shape: b,
+                    waypoints: b_waypoints,
+                    ..
+                },
+            ) => a.approx_eq(b, epsilon) && points_eq(a_waypoints, b_waypoints),
+            _ => match (self.shape(), other.shape()) {
+                (Some(a), Some(b)) => a.approx_eq(b, epsilon),
+                _ => match (self.position(), other.position()) {
+                    (Some(a), Some(b)) => a.approx_eq(&b, epsilon),
+                    _ => false,
+                },
+            },
+        }
+    }
+    /// Rounds every position and vertex to the nearest multiple of `grid`,
+    /// including circle centers and radii
+    ///
+    /// # Panics
+    ///
+    /// Panics if `grid` isn't positive.
+    #[cfg(feature = "std")]
+    pub fn snap_to_grid(&mut self, grid: f32) {
+        assert!(grid > 0.0, "grid must be positive");
+        let snap = |v: &mut f32| *v = (*v / grid).round() * grid;
+        let snap_point = |p: &mut Point| {
+            snap(&mut p.x);
+            snap(&mut p.y);
+        };
+        match self {
+            Self::Player { x, y, .. }
+            | Self::Door { x, y, .. }
+            | Self::Text { x, y, .. }
+            | Self::Boost { x, y, .. }
+            | Self::Pickup { x, y, .. } => {
+                snap(x);
+                snap(y);
+            }
+            Self::Paint { vertices, .. } => vertices.iter_mut().for_each(snap_point),
+            Self::MovingPlatform { waypoints, .. } => waypoints.iter_mut().for_each(snap_point),
+            _ => {}
+        }
+        match self.shape_mut() {
+            Some(Shape::Polygon { vertices }) => vertices.iter_mut().for_each(snap_point),
+            Some(Shape::Circle { x, y, radius }) => {
+                snap(x);
+                snap(y);
+                snap(radius);
+            }
+            None => {}
+        }
+    }
+}
+
+/// Error returned when an [`Entity`] method is called on the wrong variant
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct WrongVariantError {
+    /// Serialized type name of the variant the method needs
+    pub expected: &'static str,
+}
+
+impl fmt::Display for WrongVariantError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "expected a {} entity", self.expected)
+    }
+}
+
+impl Error for WrongVariantError {}
+
+/// Error returned by [`Entity::new_text_multi`] when given no translations
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct NoTranslationsError;
+
+impl fmt::Display for NoTranslationsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "text needs at least one translation")
+    }
+}
+
+impl Error for NoTranslationsError {}
+
+/// Error returned by [`Entity::new_moving_platform`] when given fewer than two
+/// waypoints
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct TooFewWaypointsError {
+    /// Number of waypoints that were given
+    pub count: usize,
+}
+
+impl fmt::Display for TooFewWaypointsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "moving platform has {} waypoints, expected at least 2",
+            self.count
+        )
+    }
+}
+
+impl Error for TooFewWaypointsError {}
+
+/// Error returned by [`Level::load`] and [`Level::from_json_limited`]
+#[derive(Debug)]
+pub enum LoadError {
+    /// The file couldn't be read
+    #[cfg(feature = "std")]
+    Io(io::Error),
+    /// The input isn't a valid level
+    Json(serde_json::Error),
+    /// The level has `count` entities, more than the `max` allowed
+    TooManyEntities { count: usize, max: usize },
+    /// The level has `count` vertices and waypoints, more than the `max` allowed
+    TooManyVertices { count: usize, max: usize },
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            #[cfg(feature = "std")]
+            Self::Io(err) => write!(f, "failed to read level: {}", err),
+            Self::Json(err) => write!(f, "failed to parse level: {}", err),
+            Self::TooManyEntities { count, max } => {
+                write!(f, "level has {} entities, limit is {}", count, max)
+            }
+            Self::TooManyVertices { count, max } => {
+                write!(f, "level has {} vertices, limit is {}", count, max)
+            }
+        }
+    }
+}
+
+impl Error for LoadError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            #[cfg(feature = "std")]
+            Self::Io(err) => Some(err),
+            Self::Json(err) => Some(err),
+            Self::TooManyEntities { .. } | Self::TooManyVertices { .. } => None,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<io::Error> for LoadError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for LoadError {
+    fn from(err: serde_json::Error) -> Self {
+        Self::Json(err)
+    }
+}
+
+/// Problem found by [`Level::validate`]
+#[derive(Debug, PartialEq)]
+pub enum ValidationError {
+    /// There's no `Player` entity
+    NoPlayer,
+    /// There's more than one `Player` entity
+    MultiplePlayers { count: usize },
+    /// There's no `Door` entity
+    NoEndpoint,
+    /// The `Text` or `Paint` at `index` has opacity outside `0.0..=1.0`
+    InvalidOpacity { index: usize, opacity: f32 },
+    /// The `Player` at `player` starts inside the `Deadly` at `deadly`
+    PlayerInDeadly { player: usize, deadly: usize },
+    /// The entities at `first` and `second` have the exact same position
+    DuplicatePosition { first: usize, second: usize },
+    /// The `MovingPlatform` at `index` has fewer than two waypoints
+    TooFewWaypoints { index: usize, count: usize },
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoPlayer => write!(f, "level has no player"),
+            Self::MultiplePlayers { count } => {
+                write!(f, "level has {} players, expected 1", count)
+            }
+            Self::NoEndpoint => write!(f, "level has no endpoint"),
+            Self::InvalidOpacity { index, opacity } => write!(
+                f,
+                "entity {} has opacity {}, expected 0.0 to 1.0",
+                index, opacity
+            ),
+            Self::PlayerInDeadly { player, deadly } => {
+                write!(
+                    f,
+                    "player {} starts inside deadly entity {}",
+                    player, deadly
+                )
+            }
+            Self::DuplicatePosition { first, second } => {
+                write!(f, "entities {} and {} share a position", first, second)
+            }
+            Self::TooFewWaypoints { index, count } => write!(
+                f,
+                "moving platform {} has {} waypoints, expected at least 2",
+                index, count
+            ),
+        }
+    }
+}
+
+impl Error for ValidationError {}
+
+/// Target times for a level, serialized as `[gold, silver]`
+#[derive(Serialize, Deserialize, PartialEq, Eq, Default, Clone, Copy, Debug)]
+#[serde(into = "[i32; 2]", from = "[i32; 2]")]
+pub struct Timings {
+    pub gold: i32,
+    pub silver: i32,
+}
+
+impl From<[i32; 2]> for Timings {
+    fn from([gold, silver]: [i32; 2]) -> Self {
+        Self { gold, silver }
+    }
+}
+
+impl From<Timings> for [i32; 2] {
+    fn from(timings: Timings) -> Self {
+        [timings.gold, timings.silver]
+    }
+}
+
+/// Entities and points counted by [`Level::from_json_limited`] before parsing
+#[derive(Default)]
+struct ScanCounts {
+    entities: usize,
+    vertices: usize,
+}
+
+/// Where a value sits in a level document, as far as the limits care
+#[derive(Clone, Copy)]
+enum ScanKind {
+    Level,
+    Entities,
+    Entity,
+    Params,
+    Points,
+    Other,
+}
+
+impl ScanKind {
+    /// Kind of the value stored under `key` in a map of this kind
+    fn child(self, key: &str) -> ScanKind {
+        match (self, key) {
+            (Self::Level, "entities") => Self::Entities,
+            (Self::Entity, "params") => Self::Params,
+            (Self::Params, "vertices" | "waypoints") => Self::Points,
+            _ => Self::Other,
+        }
+    }
+}
+
+/// Walks a JSON value without building anything, counting entities and
+/// vertices along the way
+struct Scan<'a> {
+    counts: &'a mut ScanCounts,
+    kind: ScanKind,
+}
+
+impl<'de> serde::de::DeserializeSeed<'de> for Scan<'_> {
+    type Value = ();
+
+    fn deserialize<D: serde::Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+/// Reads a map key and gives the kind of the value it's paired with
+struct ScanKey(ScanKind);
+
+impl<'de> serde::de::DeserializeSeed<'de> for ScanKey {
+    type Value = ScanKind;
+
+    fn deserialize<D: serde::Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<ScanKind, D::Error> {
+        deserializer.deserialize_str(self)
+    }
+}
+
+impl serde::de::Visitor<'_> for ScanKey {
+    type Value = ScanKind;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "a string key")
+    }
+
+    fn visit_str<E: serde::de::Error>(self, key: &str) -> Result<ScanKind, E> {
+        Ok(self.0.child(key))
+    }
+}
+
+impl<'de> serde::de::Visitor<'de> for Scan<'_> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "any JSON value")
+    }
+
+    fn visit_bool<E>(self, _: bool) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_i64<E>(self, _: i64) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_u64<E>(self, _: u64) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_f64<E>(self, _: f64) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_str<E>(self, _: &str) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_unit<E>(self) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_seq<A: serde::de::SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        let element = match self.kind {
+            ScanKind::Entities => ScanKind::Entity,
+            _ => ScanKind::Other,
+        };
+        while seq
+            .next_element_seed(Scan {
+                counts: &mut *self.counts,
+                kind: element,
+            })?
+            .is_some()
+        {
+            match self.kind {
+                ScanKind::Entities => self.counts.entities += 1,
+                ScanKind::Points => self.counts.vertices += 1,
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn visit_map<A: serde::de::MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+        while let Some(kind) = map.next_key_seed(ScanKey(self.kind))? {
+            map.next_value_seed(Scan {
+                counts: &mut *self.counts,
+                kind,
+            })?;
+        }
+        Ok(())
+    }
+}
+
+/// JSON formatter that writes whole numbers without a fractional part, like the editor
+#[cfg(feature = "std")]
+struct WholeNumberFormatter;
+
+#[cfg(feature = "std")]
+impl serde_json::ser::Formatter for WholeNumberFormatter {
+    fn write_f32<W: ?Sized + io::Write>(&mut self, writer: &mut W, value: f32) -> io::Result<()> {
+        // beyond 2^24 every f32 is whole, so only shorten values that fit in an i32
+        if value.fract() == 0.0 && value.abs() <= i32::MAX as f32 {
+            write!(writer, "{}", value as i64)
+        } else {
+            serde_json::ser::CompactFormatter.write_f32(writer, value)
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Level {
+    pub name: String,
+    pub timings: Timings,
     pub entities: Vec<Entity>,
     format_version: u8,
+    /// Top level keys this crate doesn't model, kept so they survive a load and save
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
-impl Level {
-    pub fn new(name: String, timings: [i32; 2]) -> Self {
-        Self {
-            name,
-            timings,
-            entities: vec![],
-            format_version: 0,
-        }
+impl Level {
+    pub fn new(name: String, timings: impl Into<Timings>) -> Self {
+        Self {
+            name,
+            timings: timings.into(),
+            entities: vec![],
+            format_version: 0,
+            extra: serde_json::Map::new(),
+        }
+    }
+    /// Starts a [`LevelBuilder`]
+    pub fn builder() -> LevelBuilder {
+        LevelBuilder::default()
+    }
+    /// Creates a level already holding `entities`
+    ///
+    /// `Level` doesn't implement `FromIterator` since a level also needs a name
+    /// and timings, use this or [`Extend`] instead.
+    pub fn with_entities<I: IntoIterator<Item = Entity>>(
+        name: String,
+        timings: impl Into<Timings>,
+        entities: I,
+    ) -> Self {
+        let mut level = Self::new(name, timings);
+        level.extend(entities);
+        level
+    }
+    /// Sets the editor format version, which defaults to 0
+    pub fn with_format_version(mut self, v: u8) -> Self {
+        self.format_version = v;
+        self
+    }
+    /// Editor format version the level is saved with
+    pub fn format_version(&self) -> u8 {
+        self.format_version
+    }
+    /// Level name shown in the editor and level list
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    /// Renames the level
+    pub fn set_name(&mut self, name: impl Into<String>) {
+        self.name = name.into();
+    }
+    /// Gold and silver target times
+    pub fn timings(&self) -> Timings {
+        self.timings
+    }
+    /// Sets the target times from a [`Timings`] or a `[gold, silver]` array
+    pub fn set_timings(&mut self, timings: impl Into<Timings>) {
+        self.timings = timings.into();
+    }
+    /// Pushes entity onto entities vector
+    pub fn push(&mut self, entity: Entity) {
+        self.entities.push(entity);
+    }
+    /// Clears all entities from entities vector
+    pub fn clear(&mut self) {
+        self.entities.clear();
+    }
+    /// Inserts entity at `index`, shifting later entities back
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index > len`.
+    pub fn insert(&mut self, index: usize, entity: Entity) {
+        self.entities.insert(index, entity);
+    }
+    /// Removes and returns the entity at `index`, shifting later entities forward
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn remove(&mut self, index: usize) -> Entity {
+        self.entities.remove(index)
+    }
+    /// Removes and returns the entity at `index`, replacing it with the last entity
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn swap_remove(&mut self, index: usize) -> Entity {
+        self.entities.swap_remove(index)
+    }
+    /// Moves every entity of `other` onto the end of this level. Only the
+    /// entities are taken, the name, timings and format version stay as they are.
+    pub fn append(&mut self, other: Level) {
+        self.extend(other);
+    }
+    /// Copy of this level with `other`'s entities moved by `offset` added to the
+    /// end, keeping this level's name, timings and format version
+    pub fn merged_with(&self, other: &Level, offset: Point) -> Level {
+        let mut merged = self.clone();
+        merged.extend(other.iter().cloned().map(|mut e| {
+            e.translate(offset);
+            e
+        }));
+        merged
+    }
+    /// Keeps only the entities matching `f`
+    pub fn retain<F: FnMut(&Entity) -> bool>(&mut self, f: F) {
+        self.entities.retain(f);
+    }
+    /// Removes entities matching an earlier one within `epsilon`, see
+    /// [`Entity::approx_eq`]
+    pub fn dedup_entities(&mut self, epsilon: f32) {
+        let mut kept: Vec<Entity> = Vec::with_capacity(self.len());
+        for entity in self.entities.drain(..) {
+            if !kept.iter().any(|k| k.approx_eq(&entity, epsilon)) {
+                kept.push(entity);
+            }
+        }
+        self.entities = kept;
+    }
+    /// Stably reorders the entities whose [`Entity::type_name`] is in `order`
+    /// so earlier names are drawn first. They only move between the slots they
+    /// already took up, unlisted types stay where they are.
+    pub fn sort_by_type(&mut self, order: &[&str]) {
+        let ranks: Vec<_> = self
+            .iter()
+            .map(|e| order.iter().position(|&name| name == e.type_name()))
+            .collect();
+        let slots: Vec<usize> = (0..ranks.len()).filter(|&i| ranks[i].is_some()).collect();
+        let mut sorted = slots.clone();
+        sorted.sort_by_key(|&i| ranks[i]);
+
+        let mut source: Vec<usize> = (0..ranks.len()).collect();
+        for (&slot, &from) in slots.iter().zip(&sorted) {
+            source[slot] = from;
+        }
+        let mut entities: Vec<_> = self.entities.drain(..).map(Some).collect();
+        self.entities = source
+            .into_iter()
+            .map(|i| entities[i].take().expect("each entity is moved once"))
+            .collect();
+    }
+    /// References to the entities matching `f`
+    pub fn filtered<F: FnMut(&Entity) -> bool>(&self, mut f: F) -> Vec<&Entity> {
+        self.iter().filter(|e| f(e)).collect()
+    }
+    /// Iterates over the entities
+    pub fn iter(&self) -> core::slice::Iter<'_, Entity> {
+        self.entities.iter()
+    }
+    /// Iterates mutably over the entities
+    pub fn iter_mut(&mut self) -> core::slice::IterMut<'_, Entity> {
+        self.entities.iter_mut()
+    }
+    /// Number of entities in the level
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+    /// Whether the level has no entities
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+    /// Serializes the level into compact JSON
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string(self)
+    }
+    /// Serializes the level into JSON indented with two spaces
+    pub fn to_json_pretty(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+    /// Serializes the level into compact JSON, writing whole coordinates such as
+    /// `400.0` as `400` the way the editor does
+    #[cfg(feature = "std")]
+    pub fn to_json_compact_numbers(&self) -> Result<String, serde_json::Error> {
+        let mut out = Vec::new();
+        self.serialize(&mut serde_json::Serializer::with_formatter(
+            &mut out,
+            WholeNumberFormatter,
+        ))?;
+        Ok(String::from_utf8(out).expect("serde_json writes valid UTF-8"))
+    }
+    /// Serializes the level as compact JSON into `writer`
+    #[cfg(feature = "std")]
+    pub fn write_to<W: io::Write>(&self, writer: W) -> Result<(), serde_json::Error> {
+        serde_json::to_writer(writer, self)
+    }
+    /// Serializes the level as JSON indented with two spaces into `writer`
+    #[cfg(feature = "std")]
+    pub fn write_to_pretty<W: io::Write>(&self, writer: W) -> Result<(), serde_json::Error> {
+        serde_json::to_writer_pretty(writer, self)
+    }
+    /// Moves every entity by `delta`
+    pub fn translate_all(&mut self, delta: Point) {
+        self.entities.iter_mut().for_each(|e| e.translate(delta));
+    }
+    /// Rotates every entity by `radians` about `about`, see [`Entity::rotate`]
+    #[cfg(feature = "std")]
+    pub fn rotate_all(&mut self, radians: f32, about: Point) {
+        self.entities
+            .iter_mut()
+            .for_each(|e| e.rotate(radians, about));
+    }
+    /// Rounds every coordinate to the nearest multiple of `grid`, see
+    /// [`Entity::snap_to_grid`]
+    ///
+    /// # Panics
+    ///
+    /// Panics if `grid` isn't positive.
+    #[cfg(feature = "std")]
+    pub fn snap_to_grid(&mut self, grid: f32) {
+        assert!(grid > 0.0, "grid must be positive");
+        self.entities.iter_mut().for_each(|e| e.snap_to_grid(grid));
+    }
+    /// Scales every entity by `factor` about `about`, see [`Entity::scale`].
+    /// Circle radii scale too, `angle` fields are left unchanged.
+    pub fn scale_all(&mut self, factor: f32, about: Point) {
+        self.entities
+            .iter_mut()
+            .for_each(|e| e.scale(factor, about));
+    }
+    /// Reflects every entity about the vertical line `x = axis_x`, see
+    /// [`Entity::mirror_horizontal`]
+    pub fn mirror_horizontal(&mut self, axis_x: f32) {
+        self.entities
+            .iter_mut()
+            .for_each(|e| e.mirror_horizontal(axis_x));
+    }
+    /// Reflects every entity about the horizontal line `y = axis_y`, see
+    /// [`Entity::mirror_vertical`]
+    pub fn mirror_vertical(&mut self, axis_y: f32) {
+        self.entities
+            .iter_mut()
+            .for_each(|e| e.mirror_vertical(axis_y));
+    }
+    /// Bounding box of every entity as `(min, max)`, `None` if the level has
+    /// no entities with a position
+    pub fn bounding_box(&self) -> Option<(Point, Point)> {
+        self.entities
+            .iter()
+            .filter_map(Entity::bounding_box)
+            .reduce(union_bounds)
+    }
+    /// Centroid of every material and moving platform shape weighted by area,
+    /// `None` if the level has no solid geometry. `Paint` is decoration and
+    /// doesn't count.
+    pub fn center_of_mass(&self) -> Option<Point> {
+        let (sum, mass) =
+            self.iter()
+                .filter_map(Entity::shape)
+                .fold((Point::ZERO, 0.0), |(sum, mass), shape| {
+                    let area = shape.area();
+                    (sum + shape.centroid() * area, mass + area)
+                });
+        (mass > 0.0).then(|| sum / mass)
+    }
+    /// Whether the straight line from the player to the nearest endpoint misses
+    /// every solid shape, see [`Shape::intersects_segment`], `false` if either
+    /// is missing. Only `Bouncy` shapes are ignored. This is a cheap heuristic
+    /// for generated levels, not a physics check: the hopper flies in arcs and
+    /// can bomb its way around walls.
+    pub fn endpoint_line_of_sight(&self) -> bool {
+        let Some(start) = self.player().and_then(Entity::position) else {
+            return false;
+        };
+        let Some(end) = self
+            .endpoints()
+            .into_iter()
+            .filter_map(Entity::position)
+            .min_by(|a, b| {
+                a.distance_squared(&start)
+                    .total_cmp(&b.distance_squared(&start))
+            })
+        else {
+            return false;
+        };
+        !self
+            .iter()
+            .filter(|e| !matches!(e, Entity::Bouncy { .. }))
+            .filter_map(Entity::shape)
+            .any(|shape| shape.intersects_segment(&start, &end))
+    }
+    /// Checks the level can be loaded by the editor, collecting every problem found
+    pub fn validate(&self) -> Result<(), Vec<ValidationError>> {
+        let mut errors = vec![];
+
+        let players = self
+            .iter()
+            .filter(|e| matches!(e, Entity::Player { .. }))
+            .count();
+        match players {
+            0 => errors.push(ValidationError::NoPlayer),
+            1 => {}
+            count => errors.push(ValidationError::MultiplePlayers { count }),
+        }
+
+        if !self.iter().any(|e| matches!(e, Entity::Door { .. })) {
+            errors.push(ValidationError::NoEndpoint);
+        }
+
+        for (index, entity) in self.iter().enumerate() {
+            if let Entity::Text { opacity, .. } | Entity::Paint { opacity, .. } = entity {
+                if !(0.0..=1.0).contains(opacity) {
+                    errors.push(ValidationError::InvalidOpacity {
+                        index,
+                        opacity: *opacity,
+                    });
+                }
+            }
+            if let Entity::MovingPlatform { waypoints, .. } = entity {
+                if waypoints.len() < 2 {
+                    errors.push(ValidationError::TooFewWaypoints {
+                        index,
+                        count: waypoints.len(),
+                    });
+                }
+            }
+        }
+
+        for (player, entity) in self.iter().enumerate() {
+            let Entity::Player { x, y, .. } = entity else {
+                continue;
+            };
+            for (deadly, other) in self.iter().enumerate() {
+                if let Entity::Deadly { shape, .. } = other {
+                    if shape.contains_point(&Point::new(*x, *y)) {
+                        errors.push(ValidationError::PlayerInDeadly { player, deadly });
+                    }
+                }
+            }
+        }
+
+        let positions: Vec<_> = self.iter().map(Entity::position).collect();
+        for (first, a) in positions.iter().enumerate() {
+            for (second, b) in positions.iter().enumerate().skip(first + 1) {
+                if a.is_some() && a == b {
+                    errors.push(ValidationError::DuplicatePosition { first, second });
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+    /// First entity matching `f`
+    pub fn find<F: Fn(&Entity) -> bool>(&self, f: F) -> Option<&Entity> {
+        self.entities.iter().find(|e| f(e))
+    }
+    /// First entity matching `f`, mutably
+    pub fn find_mut<F: Fn(&Entity) -> bool>(&mut self, f: F) -> Option<&mut Entity> {
+        self.entities.iter_mut().find(|e| f(e))
+    }
+    /// The first `Player`, levels the editor accepts have exactly one
+    pub fn player(&self) -> Option<&Entity> {
+        self.find(|e| matches!(e, Entity::Player { .. }))
+    }
+    /// Every `Door` in draw order
+    pub fn endpoints(&self) -> Vec<&Entity> {
+        self.entities
+            .iter()
+            .filter(|e| matches!(e, Entity::Door { .. }))
+            .collect()
+    }
+    /// Indices of the entities containing `p` in draw order, so the topmost is
+    /// last. See [`Entity::contains_point`].
+    pub fn entities_at(&self, p: &Point) -> Vec<usize> {
+        self.iter()
+            .enumerate()
+            .filter(|(_, e)| e.contains_point(p))
+            .map(|(i, _)| i)
+            .collect()
+    }
+    /// Number of entities of each kind, keyed by [`Entity::type_name`]
+    #[cfg(feature = "std")]
+    pub fn count_by_type(&self) -> HashMap<&'static str, usize> {
+        let mut counts = HashMap::new();
+        for entity in self {
+            *counts.entry(entity.type_name()).or_insert(0) += 1;
+        }
+        counts
+    }
+    /// Deserializes a level from JSON
+    pub fn from_json(s: &str) -> Result<Level, serde_json::Error> {
+        serde_json::from_str(s)
+    }
+    /// Deserializes a level from untrusted JSON, rejecting it if it has more
+    /// than `max_entities` entities or `max_vertices` vertices and waypoints in
+    /// total. The limits are checked by a first pass over `s` that builds no
+    /// level, so an oversized one is rejected up front.
+    pub fn from_json_limited(
+        s: &str,
+        max_entities: usize,
+        max_vertices: usize,
+    ) -> Result<Level, LoadError> {
+        let mut counts = ScanCounts::default();
+        serde::de::DeserializeSeed::deserialize(
+            Scan {
+                counts: &mut counts,
+                kind: ScanKind::Level,
+            },
+            &mut serde_json::Deserializer::from_str(s),
+        )?;
+        if counts.entities > max_entities {
+            return Err(LoadError::TooManyEntities {
+                count: counts.entities,
+                max: max_entities,
+            });
+        }
+        if counts.vertices > max_vertices {
+            return Err(LoadError::TooManyVertices {
+                count: counts.vertices,
+                max: max_vertices,
+            });
+        }
+        Ok(Self::from_json(s)?)
+    }
+    /// Converts the level into a JSON tree without going through a string.
+    /// Coordinates are widened to `f64`, so `0.1` shows up as `0.10000000149011612`.
+    pub fn to_value(&self) -> Result<serde_json::Value, serde_json::Error> {
+        serde_json::to_value(self)
+    }
+    /// Deserializes a level from a JSON tree
+    pub fn from_value(v: serde_json::Value) -> Result<Level, serde_json::Error> {
+        serde_json::from_value(v)
+    }
+    /// Writes the level as JSON to `path`, creating parent directories as needed
+    #[cfg(feature = "std")]
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, self.to_json()?)
+    }
+    /// Reads and deserializes a level from the JSON file at `path`
+    #[cfg(feature = "std")]
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Level, LoadError> {
+        let json = fs::read_to_string(path)?;
+        Ok(Level::from_json(&json)?)
+    }
+}
+
+/// Entities transformed together before being added to a level. Groups only
+/// exist while building, they're flattened with [`Group::into_entities`].
+#[derive(Default, Clone, Debug)]
+pub struct Group {
+    pub entities: Vec<Entity>,
+}
+
+impl Group {
+    pub fn new(entities: Vec<Entity>) -> Self {
+        Self { entities }
+    }
+    /// Moves every member by `delta`
+    pub fn translate(&mut self, delta: Point) {
+        self.entities.iter_mut().for_each(|e| e.translate(delta));
+    }
+    /// Rotates every member by `radians` about `about`, see [`Entity::rotate`]
+    #[cfg(feature = "std")]
+    pub fn rotate(&mut self, radians: f32, about: Point) {
+        self.entities
+            .iter_mut()
+            .for_each(|e| e.rotate(radians, about));
+    }
+    /// Scales every member by `factor` about `about`, see [`Entity::scale`]
+    pub fn scale(&mut self, factor: f32, about: Point) {
+        self.entities
+            .iter_mut()
+            .for_each(|e| e.scale(factor, about));
+    }
+    pub fn into_entities(self) -> Vec<Entity> {
+        self.entities
+    }
+}
+
+/// Levels shipped together, serialized as the plain JSON array of levels the
+/// game reads. The array has nowhere to put a pack name, so there isn't one.
+#[derive(Serialize, Deserialize, PartialEq, Default, Clone, Debug)]
+#[serde(transparent)]
+pub struct LevelPack {
+    pub levels: Vec<Level>,
+}
+
+impl LevelPack {
+    pub fn new(levels: Vec<Level>) -> Self {
+        Self { levels }
+    }
+    /// Serializes the pack into a compact JSON array
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string(self)
+    }
+    /// Deserializes a pack from a JSON array of levels
+    pub fn from_json(s: &str) -> Result<LevelPack, serde_json::Error> {
+        serde_json::from_str(s)
+    }
+}
+
+/// Fluent builder for a [`Level`], defaulting to an empty name and `[0, 0]` timings
+#[derive(Default, Clone, Debug)]
+pub struct LevelBuilder {
+    name: String,
+    timings: Timings,
+    entities: Vec<Entity>,
+}
+
+impl LevelBuilder {
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+    pub fn timings(mut self, timings: impl Into<Timings>) -> Self {
+        self.timings = timings.into();
+        self
+    }
+    /// Adds an entity after the ones already added
+    pub fn entity(mut self, entity: Entity) -> Self {
+        self.entities.push(entity);
+        self
+    }
+    /// Adds entities after the ones already added
+    pub fn entities<I: IntoIterator<Item = Entity>>(mut self, entities: I) -> Self {
+        self.entities.extend(entities);
+        self
+    }
+    pub fn build(self) -> Level {
+        Level::with_entities(self.name, self.timings, self.entities)
+    }
+}
+
+impl Index<usize> for Level {
+    type Output = Entity;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.entities[index]
+    }
+}
+
+impl IndexMut<usize> for Level {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        &mut self.entities[index]
+    }
+}
+
+impl Extend<Entity> for Level {
+    fn extend<I: IntoIterator<Item = Entity>>(&mut self, iter: I) {
+        self.entities.extend(iter);
+    }
+}
+
+impl IntoIterator for Level {
+    type Item = Entity;
+    type IntoIter = vec::IntoIter<Entity>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entities.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Level {
+    type Item = &'a Entity;
+    type IntoIter = core::slice::Iter<'a, Entity>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a mut Level {
+    type Item = &'a mut Entity;
+    type IntoIter = core::slice::IterMut<'a, Entity>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::format;
+
+    #[test]
+    fn ammo() {
+        // Tests if infinte bombs goes correctly
+        assert_eq!(
+            r#"{"type":"player","params":{"isStatic":false,"angle":0,"x":0.0,"y":0.0,"infiniteAmmo":"bullet"}}"#,
+            serde_json::to_string(&Entity::Player {
+                is_static: false,
+                angle: 0,
+                x: 0.0,
+                y: 0.0,
+                ammo: Ammo::Infinite(AmmoType::Bomb),
+                extra: serde_json::Map::new(),
+                note: None,
+            })
+            .unwrap()
+        );
+
+        // Tests if finite seq works (and finite)
+        assert_eq!(
+            r#"{"type":"player","params":{"isStatic":false,"angle":0,"x":0.0,"y":0.0,"magazine":["grenade","empty","bullet","bullet"]}}"#,
+            serde_json::to_string(&Entity::Player {
+                is_static: false,
+                angle: 0,
+                x: 0.0,
+                y: 0.0,
+                ammo: Ammo::finite_seq("bbeg").unwrap(),
+                extra: serde_json::Map::new(),
+                note: None,
+            })
+            .unwrap()
+        );
+
+        // Tests if unknown ammo reports where it failed
+        assert_eq!(
+            AmmoParseError::UnknownChar { ch: 'x', index: 2 },
+            Ammo::finite_seq("bgxe").unwrap_err()
+        );
+        assert_eq!(
+            "unknown ammo type 'x' at index 2",
+            AmmoParseError::UnknownChar { ch: 'x', index: 2 }.to_string()
+        );
+    }
+
+    const DEFAULT_LEVEL: &str = r#"{"name":"My level","timings":[0,0],"entities":[{"type":"text","params":{"angle":0,"x":200.0,"y":520.0,"copy":{"en":"This is the default level!\nEdit to your liking"},"anchor":{"x":0.5,"y":0.5},"align":"left","fillColor":16777215,"opacity":1.0}},{"type":"normal","params":{"isStatic":true,"vertices":[{"x":400.0,"y":820.0},{"x":400.0,"y":880.0},{"x":520.0,"y":880.0},{"x":520.0,"y":820.0}]}},{"type":"ice","params":{"isStatic":true,"vertices":[{"x":-260.0,"y":580.0},{"x":-260.0,"y":820.0},{"x":400.0,"y":820.0},{"x":400.0,"y":760.0},{"x":160.0,"y":760.0},{"x":-20.0,"y":640.0},{"x":-140.0,"y":640.0}]}},{"type":"endpoint","params":{"isStatic":true,"angle":0,"x":550.0,"y":630.0,"rightFacing":true}},{"type":"player","params":{"isStatic":false,"angle":0,"x":-60.0,"y":620.0,"magazine":["grenade","empty","bullet"]}},{"type":"normal","params":{"isStatic":false,"vertices":[{"x":-236.0,"y":292.0},{"x":-176.0,"y":292.0},{"x":-176.0,"y":352.0},{"x":-236.0,"y":352.0}]}}],"formatVersion":0}"#;
+
+    /// Builds the level the editor starts out with
+    fn build_default_level() -> Level {
+        let mut level = Level::new(String::from("My level"), [0, 0]);
+        level.push(Entity::new_text(
+            Point::new(200.0, 520.0),
+            "This is the default level!\nEdit to your liking",
+        ));
+
+        level.push(Entity::Normal {
+            is_static: true,
+            shape: Shape::Polygon {
+                vertices: vec![
+                    Point::new(400.0, 820.0),
+                    Point::new(400.0, 880.0),
+                    Point::new(520.0, 880.0),
+                    Point::new(520.0, 820.0),
+                ],
+            },
+            extra: serde_json::Map::new(),
+            note: None,
+        });
+
+        level.push(Entity::Ice {
+            is_static: true,
+            shape: Shape::Polygon {
+                vertices: vec![
+                    Point::new(-260.0, 580.0),
+                    Point::new(-260.0, 820.0),
+                    Point::new(400.0, 820.0),
+                    Point::new(400.0, 760.0),
+                    Point::new(160.0, 760.0),
+                    Point::new(-20.0, 640.0),
+                    Point::new(-140.0, 640.0),
+                ],
+            },
+            extra: serde_json::Map::new(),
+            note: None,
+        });
+
+        level.push(Entity::Door {
+            is_static: true,
+            angle: 0,
+            x: 550.0,
+            y: 630.0,
+            right_facing: true,
+            extra: serde_json::Map::new(),
+            note: None,
+        });
+
+        level.push(Entity::Player {
+            is_static: false,
+            angle: 0,
+            x: -60.0,
+            y: 620.0,
+            ammo: Ammo::finite_seq("beg").unwrap(),
+            extra: serde_json::Map::new(),
+            note: None,
+        });
+
+        level.push(Entity::Normal {
+            is_static: false,
+            shape: Shape::Polygon {
+                vertices: vec![
+                    Point::new(-236.0, 292.0),
+                    Point::new(-176.0, 292.0),
+                    Point::new(-176.0, 352.0),
+                    Point::new(-236.0, 352.0),
+                ],
+            },
+            extra: serde_json::Map::new(),
+            note: None,
+        });
+
+        level
+    }
+
+    #[test]
+    fn default_level() {
+        assert_eq!(
+            DEFAULT_LEVEL,
+            serde_json::to_string(&build_default_level()).unwrap()
+        );
+    }
+
+    #[test]
+    fn deserialize_default_level() {
+        let level: Level = serde_json::from_str(DEFAULT_LEVEL).unwrap();
+        assert_eq!(build_default_level(), level);
+
+        // Magazine must come back in stored order, not reversed a second time
+        match &level.entities[4] {
+            Entity::Player {
+                ammo: Ammo::Finite(mag),
+                ..
+            } => assert!(matches!(
+                mag.as_slice(),
+                [AmmoType::Grenade, AmmoType::Empty, AmmoType::Bomb]
+            )),
+            _ => panic!("expected player with finite ammo"),
+        }
+    }
+
+    #[test]
+    fn deserialize_editor_export() {
+        // Exported from the editor, covers every entity type and both shapes
+        let level: Level = serde_json::from_str(include_str!("../all.json")).unwrap();
+        assert_eq!(14, level.entities.len());
+        assert!(matches!(
+            level.entities[0],
+            Entity::Player {
+                ammo: Ammo::Infinite(AmmoType::Bomb),
+                ..
+            }
+        ));
+        assert!(matches!(
+            level.entities[4],
+            Entity::Normal {
+                shape: Shape::Polygon { .. },
+                ..
+            }
+        ));
+        assert!(matches!(
+            level.entities[13],
+            Entity::Bouncy {
+                shape: Shape::Circle { .. },
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn json_helpers() {
+        let mut level = Level::new(String::from("One"), [1, 2]);
+        level.push(Entity::Door {
+            is_static: true,
+            angle: 0,
+            x: 1.0,
+            y: 2.0,
+            right_facing: false,
+            extra: serde_json::Map::new(),
+            note: None,
+        });
+
+        assert_eq!(
+            r#"{"name":"One","timings":[1,2],"entities":[{"type":"endpoint","params":{"isStatic":true,"angle":0,"x":1.0,"y":2.0,"rightFacing":false}}],"formatVersion":0}"#,
+            level.to_json().unwrap()
+        );
+
+        assert_eq!(
+            r#"{
+  "name": "One",
+  "timings": [
+    1,
+    2
+  ],
+  "entities": [
+    {
+      "type": "endpoint",
+      "params": {
+        "isStatic": true,
+        "angle": 0,
+        "x": 1.0,
+        "y": 2.0,
+        "rightFacing": false
+      }
+    }
+  ],
+  "formatVersion": 0
+}"#,
+            level.to_json_pretty().unwrap()
+        );
+
+        assert_eq!(
+            DEFAULT_LEVEL,
+            Level::from_json(DEFAULT_LEVEL).unwrap().to_json().unwrap()
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn save_and_load() {
+        let dir = std::env::temp_dir().join(format!("bombhopper-{}", std::process::id()));
+        let path = dir.join("levels").join("default.json");
+
+        build_default_level().save(&path).unwrap();
+        let level = Level::load(&path).unwrap();
+        assert_eq!(DEFAULT_LEVEL, level.to_json().unwrap());
+
+        assert!(matches!(
+            Level::load(dir.join("missing.json")),
+            Err(LoadError::Io(_))
+        ));
+
+        fs::write(&path, "{").unwrap();
+        assert!(matches!(Level::load(&path), Err(LoadError::Json(_))));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn clone_level() {
+        let level = build_default_level();
+        let mut variant = level.clone();
+        assert_eq!(level.to_json().unwrap(), variant.to_json().unwrap());
+
+        // Changing the clone leaves the original alone
+        variant.clear();
+        assert_eq!(DEFAULT_LEVEL, level.to_json().unwrap());
+        assert!(format!("{:?}", level).starts_with("Level { name: \"My level\""));
+    }
+
+    #[test]
+    fn color() {
+        assert_eq!(16777215, Color::rgb(255, 255, 255).to_packed());
+        assert_eq!(0, i32::from(Color::BLACK));
+        assert_eq!(Color::rgb(255, 136, 0), Color::from_hex("#ff8800").unwrap());
+        assert_eq!(0xff8800, Color::from_hex("FF8800").unwrap().to_packed());
+        assert_eq!(Color::rgb(255, 136, 0), Color::from(0xff8800));
+
+        assert_eq!(
+            ColorError::InvalidLength { len: 3 },
+            Color::from_hex("#fff").unwrap_err()
+        );
+        assert_eq!(
+            ColorError::InvalidDigit { ch: 'g', index: 2 },
+            Color::from_hex("#fgffff").unwrap_err()
+        );
+
+        assert_eq!("16777215", serde_json::to_string(&Color::WHITE).unwrap());
+        assert_eq!(
+            Color::rgb(255, 136, 0),
+            serde_json::from_str("16746496").unwrap()
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn point_distance() {
+        let a = Point::new(1.0, 2.0);
+        let b = Point::new(4.0, 6.0);
+        assert_eq!(5.0, a.distance(&b));
+        assert_eq!(5.0, b.distance(&a));
+        assert_eq!(25.0, a.distance_squared(&b));
+        assert_eq!(0.0, a.distance(&a));
+    }
+
+    #[test]
+    fn point_tuple() {
+        let point = Point::from((1.0, 2.0));
+        assert_eq!(1.0, point.x());
+        assert_eq!(2.0, point.y());
+
+        let (x, y): (f32, f32) = point.into();
+        assert_eq!((1.0, 2.0), (x, y));
+        assert_eq!(point, Point::from((x, y)));
+    }
+
+    #[test]
+    fn point_neg_div() {
+        assert_eq!(Point::new(-1.0, 2.0), -Point::new(1.0, -2.0));
+        assert_eq!(Point::new(2.0, 4.0), Point::new(4.0, 8.0) / 2.0);
+        assert_eq!(f32::INFINITY, (Point::new(1.0, 0.0) / 0.0).x);
+    }
+
+    #[test]
+    fn point_assign() {
+        let mut point = Point::new(1.0, 1.0);
+        point += Point::new(2.0, 3.0);
+        point -= Point::new(1.0, 0.0);
+        point *= 2.0;
+        assert_eq!(Point::new(4.0, 8.0), point);
+    }
+
+    #[test]
+    fn point_dot_cross() {
+        let a = Point::new(1.0, 0.0);
+        let b = Point::new(0.0, 1.0);
+        assert_eq!(0.0, a.dot(&b));
+        assert_eq!(11.0, Point::new(1.0, 2.0).dot(&Point::new(3.0, 4.0)));
+        assert_eq!(1.0, a.cross(&b));
+        assert_eq!(-1.0, b.cross(&a));
+        assert_eq!(-2.0, Point::new(1.0, 2.0).cross(&Point::new(3.0, 4.0)));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn point_rotate() {
+        use core::f32::consts::FRAC_PI_2;
+
+        let rotated = Point::new(1.0, 0.0).rotate(FRAC_PI_2);
+        assert!(rotated.distance(&Point::new(0.0, 1.0)) < 1e-6);
+
+        let rotated = Point::new(2.0, 1.0).rotate_around(&Point::new(1.0, 1.0), FRAC_PI_2);
+        assert!(rotated.distance(&Point::new(1.0, 2.0)) < 1e-6);
+    }
+
+    #[test]
+    fn shape_rectangle() {
+        let expected = vec![
+            Point::new(400.0, 820.0),
+            Point::new(400.0, 880.0),
+            Point::new(520.0, 880.0),
+            Point::new(520.0, 820.0),
+        ];
+
+        let Shape::Polygon { vertices } = Shape::rectangle(Point::new(460.0, 850.0), 120.0, 60.0)
+        else {
+            panic!("expected polygon");
+        };
+        assert_eq!(expected, vertices);
+
+        let Shape::Polygon { vertices } =
+            Shape::rectangle_corners(Point::new(400.0, 820.0), Point::new(520.0, 880.0))
+        else {
+            panic!("expected polygon");
+        };
+        assert_eq!(expected, vertices);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn shape_regular_polygon() {
+        let center = Point::new(10.0, -5.0);
+        for sides in [4, 6] {
+            let Shape::Polygon { vertices } = Shape::regular_polygon(center, 3.0, sides) else {
+                panic!("expected polygon");
+            };
+            assert_eq!(sides as usize, vertices.len());
+            for vertex in vertices {
+                assert!((vertex.distance(&center) - 3.0).abs() < 1e-5);
+            }
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    #[should_panic(expected = "at least 3 sides")]
+    fn shape_regular_polygon_too_few_sides() {
+        Shape::regular_polygon(Point::default(), 1.0, 2);
+    }
+
+    #[test]
+    fn shape_area() {
+        let square = Shape::rectangle_corners(Point::new(0.0, 0.0), Point::new(1.0, 1.0));
+        assert_eq!(1.0, square.area());
+
+        let triangle = Shape::Polygon {
+            vertices: vec![
+                Point::new(0.0, 0.0),
+                Point::new(4.0, 0.0),
+                Point::new(0.0, 3.0),
+            ],
+        };
+        assert_eq!(6.0, triangle.area());
+
+        let circle = Shape::Circle {
+            x: 1.0,
+            y: 1.0,
+            radius: 2.0,
+        };
+        assert!((circle.area() - 4.0 * core::f32::consts::PI).abs() < 1e-5);
+
+        assert_eq!(0.0, Shape::Polygon { vertices: vec![] }.area());
+    }
+
+    #[test]
+    fn shape_centroid() {
+        let square = Shape::rectangle(Point::default(), 2.0, 2.0);
+        assert_eq!(Point::new(0.0, 0.0), square.centroid());
+
+        let triangle = Shape::Polygon {
+            vertices: vec![
+                Point::new(0.0, 0.0),
+                Point::new(3.0, 0.0),
+                Point::new(0.0, 3.0),
+            ],
+        };
+        assert_eq!(Point::new(1.0, 1.0), triangle.centroid());
+
+        let circle = Shape::Circle {
+            x: 5.0,
+            y: -3.0,
+            radius: 2.0,
+        };
+        assert_eq!(Point::new(5.0, -3.0), circle.centroid());
+
+        let line = Shape::Polygon {
+            vertices: vec![Point::new(0.0, 0.0), Point::new(4.0, 2.0)],
+        };
+        assert_eq!(Point::new(2.0, 1.0), line.centroid());
+    }
+
+    #[test]
+    fn shape_bounding_box() {
+        let polygon = Shape::Polygon {
+            vertices: vec![
+                Point::new(-260.0, 580.0),
+                Point::new(-260.0, 820.0),
+                Point::new(400.0, 820.0),
+                Point::new(160.0, 760.0),
+            ],
+        };
+        assert_eq!(
+            (Point::new(-260.0, 580.0), Point::new(400.0, 820.0)),
+            polygon.bounding_box()
+        );
+
+        let circle = Shape::Circle {
+            x: 10.0,
+            y: 20.0,
+            radius: 5.0,
+        };
+        assert_eq!(
+            (Point::new(5.0, 15.0), Point::new(15.0, 25.0)),
+            circle.bounding_box()
+        );
+
+        assert_eq!(
+            (Point::default(), Point::default()),
+            Shape::Polygon { vertices: vec![] }.bounding_box()
+        );
+    }
+
+    #[test]
+    fn shape_contains_point() {
+        let square = Shape::rectangle_corners(Point::new(0.0, 0.0), Point::new(10.0, 10.0));
+        assert!(square.contains_point(&Point::new(5.0, 5.0)));
+        assert!(!square.contains_point(&Point::new(15.0, 5.0)));
+        assert!(!square.contains_point(&Point::new(-0.1, 5.0)));
+        assert!(square.contains_point(&Point::new(10.0, 5.0)));
+        assert!(square.contains_point(&Point::new(0.0, 0.0)));
+
+        let concave = Shape::Polygon {
+            vertices: vec![
+                Point::new(0.0, 0.0),
+                Point::new(0.0, 10.0),
+                Point::new(10.0, 10.0),
+                Point::new(10.0, 8.0),
+                Point::new(2.0, 8.0),
+                Point::new(2.0, 0.0),
+            ],
+        };
+        assert!(concave.contains_point(&Point::new(1.0, 1.0)));
+        assert!(!concave.contains_point(&Point::new(5.0, 5.0)));
+
+        let circle = Shape::Circle {
+            x: 0.0,
+            y: 0.0,
+            radius: 2.0,
+        };
+        assert!(circle.contains_point(&Point::new(1.0, 1.0)));
+        assert!(circle.contains_point(&Point::new(2.0, 0.0)));
+        assert!(!circle.contains_point(&Point::new(2.0, 0.1)));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn shape_transforms() {
+        let mut square = Shape::rectangle_corners(Point::new(0.0, 0.0), Point::new(1.0, 1.0));
+        square.translate(Point::new(2.0, -1.0));
+        let Shape::Polygon { vertices } = &square else {
+            panic!("expected polygon");
+        };
+        assert_eq!(
+            &vec![
+                Point::new(2.0, -1.0),
+                Point::new(2.0, 0.0),
+                Point::new(3.0, 0.0),
+                Point::new(3.0, -1.0),
+            ],
+            vertices
+        );
+
+        square.scale(2.0, Point::new(2.0, -1.0));
+        assert_eq!(
+            (Point::new(2.0, -1.0), Point::new(4.0, 1.0)),
+            square.bounding_box()
+        );
+
+        let mut circle = Shape::Circle {
+            x: 1.0,
+            y: 0.0,
+            radius: 1.0,
+        };
+        circle.scale(3.0, Point::default());
+        let Shape::Circle { x, y, radius } = &circle else {
+            panic!("expected circle");
+        };
+        assert_eq!((3.0, 0.0, 3.0), (*x, *y, *radius));
+
+        circle.rotate(core::f32::consts::PI, Point::default());
+        let Shape::Circle { x, y, radius } = circle else {
+            panic!("expected circle");
+        };
+        assert!(Point::new(x, y).distance(&Point::new(-3.0, 0.0)) < 1e-5);
+        assert_eq!(3.0, radius);
+    }
+
+    #[test]
+    fn material_constructors() {
+        let shape = Shape::rectangle(Point::new(0.0, 0.0), 10.0, 10.0);
+        assert_eq!(
+            serde_json::to_string(&Entity::Normal {
+                is_static: true,
+                shape: shape.clone(),
+                extra: serde_json::Map::new(),
+                note: None,
+            })
+            .unwrap(),
+            serde_json::to_string(&Entity::new_normal(shape.clone(), true)).unwrap()
+        );
+        assert!(matches!(
+            Entity::new_deadly(shape, false),
+            Entity::Deadly {
+                is_static: false,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn entity_position() {
+        let mut door = Entity::Door {
+            is_static: true,
+            angle: 0,
+            x: 550.0,
+            y: 630.0,
+            right_facing: true,
+            extra: serde_json::Map::new(),
+            note: None,
+        };
+        assert_eq!(Some(Point::new(550.0, 630.0)), door.position());
+        door.set_position(Point::new(10.0, 20.0));
+        assert_eq!(Some(Point::new(10.0, 20.0)), door.position());
+
+        let mut normal = Entity::new_normal(Shape::rectangle(Point::new(5.0, 5.0), 2.0, 2.0), true);
+        assert_eq!(Some(Point::new(5.0, 5.0)), normal.position());
+        normal.set_position(Point::new(0.0, 0.0));
+        assert_eq!(Some(Point::new(0.0, 0.0)), normal.position());
+        let Entity::Normal { shape, .. } = &normal else {
+            panic!("expected normal");
+        };
+        assert_eq!(
+            (Point::new(-1.0, -1.0), Point::new(1.0, 1.0)),
+            shape.bounding_box()
+        );
+
+        let empty = Entity::new_ice(Shape::Polygon { vertices: vec![] }, true);
+        assert_eq!(None, empty.position());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn entity_translate() {
+        let delta = Point::new(10.0, -5.0);
+        let mut level = build_default_level();
+        level.push(Entity::Paint {
+            fill_color: Color::BLACK,
+            opacity: 1.0,
+            vertices: vec![Point::new(0.0, 0.0), Point::new(1.0, 1.0)],
+            extra: serde_json::Map::new(),
+            note: None,
+        });
+        level.push(Entity::new_bouncy(
+            Shape::Circle {
+                x: 1.0,
+                y: 2.0,
+                radius: 3.0,
+            },
+            true,
+        ));
+
+        for entity in level.entities.iter_mut() {
+            let before = entity.position().unwrap();
+            entity.translate(delta);
+            let after = entity.position().unwrap();
+            assert!(after.distance(&(before + delta)) < 1e-3, "{:?}", entity);
+        }
+
+        let Entity::Bouncy {
+            shape: Shape::Circle { x, y, radius },
+            ..
+        } = level.entities[7]
+        else {
+            panic!("expected bouncy circle");
+        };
+        assert_eq!((11.0, -3.0, 3.0), (x, y, radius));
+    }
+
+    #[test]
+    fn level_translate_all() {
+        let mut level = Level::new(String::from("Moved"), [0, 0]);
+        level.push(Entity::new_text(Point::new(1.0, 2.0), "Hi"));
+        level.push(Entity::new_normal(
+            Shape::rectangle(Point::new(0.0, 0.0), 2.0, 2.0),
+            true,
+        ));
+
+        level.translate_all(Point::new(100.0, 50.0));
+        assert_eq!(Some(Point::new(101.0, 52.0)), level.entities[0].position());
+        assert_eq!(Some(Point::new(100.0, 50.0)), level.entities[1].position());
+    }
+
+    #[test]
+    fn level_bounding_box() {
+        let mut level = Level::new(String::from("Empty"), [0, 0]);
+        assert_eq!(None, level.bounding_box());
+
+        level.push(Entity::new_normal(
+            Shape::rectangle_corners(Point::new(0.0, 0.0), Point::new(10.0, 10.0)),
+            true,
+        ));
+        level.push(Entity::new_deadly(
+            Shape::Circle {
+                x: 20.0,
+                y: 5.0,
+                radius: 2.0,
+            },
+            true,
+        ));
+        level.push(Entity::new_text(Point::new(-5.0, 30.0), "Far away"));
+        assert_eq!(
+            Some((Point::new(-5.0, 0.0), Point::new(22.0, 30.0))),
+            level.bounding_box()
+        );
+    }
+
+    #[test]
+    fn level_index() {
+        let mut level = build_default_level();
+        assert_eq!(6, level.len());
+        assert!(!level.is_empty());
+        assert!(matches!(level[3], Entity::Door { .. }));
+
+        level[3].set_position(Point::new(0.0, 0.0));
+        assert_eq!(Some(Point::new(0.0, 0.0)), level[3].position());
+
+        level.clear();
+        assert!(level.is_empty());
+    }
+
+    #[test]
+    fn level_insert_remove() {
+        let mut level = build_default_level();
+
+        assert!(matches!(level.remove(3), Entity::Door { .. }));
+        assert_eq!(5, level.len());
+        assert!(matches!(level[3], Entity::Player { .. }));
+
+        level.insert(0, Entity::new_text(Point::default(), "First"));
+        assert_eq!(6, level.len());
+        assert!(matches!(level[0], Entity::Text { .. }));
+        assert!(matches!(level[1], Entity::Text { .. }));
+
+        assert!(matches!(level.swap_remove(0), Entity::Text { .. }));
+        assert!(matches!(level[0], Entity::Normal { .. }));
+        assert_eq!(5, level.len());
+    }
+
+    #[test]
+    #[should_panic]
+    fn level_remove_out_of_bounds() {
+        Level::new(String::new(), [0, 0]).remove(0);
+    }
+
+    #[test]
+    fn level_iter() {
+        let mut level = build_default_level();
+
+        let mut count = 0;
+        for _ in &level {
+            count += 1;
+        }
+        assert_eq!(6, count);
+
+        let statics = level
+            .iter()
+            .filter(|e| {
+                matches!(
+                    e,
+                    Entity::Normal {
+                        is_static: true,
+                        ..
+                    }
+                )
+            })
+            .count();
+        assert_eq!(1, statics);
+
+        for entity in &mut level {
+            entity.translate(Point::new(1.0, 0.0));
+        }
+        level
+            .iter_mut()
+            .for_each(|e| e.translate(Point::new(-1.0, 0.0)));
+        assert_eq!(DEFAULT_LEVEL, level.to_json().unwrap());
+
+        assert_eq!(6, level.into_iter().count());
+    }
+
+    #[test]
+    fn level_extend() {
+        let entities = build_default_level().entities;
+
+        let mut level = Level::new(String::from("My level"), [0, 0]);
+        level.extend(entities.clone());
+        assert_eq!(DEFAULT_LEVEL, level.to_json().unwrap());
+
+        let level = Level::with_entities(String::from("My level"), [0, 0], entities);
+        assert_eq!(DEFAULT_LEVEL, level.to_json().unwrap());
+    }
+
+    #[test]
+    fn timings() {
+        let level = Level::new(
+            String::from("Timed"),
+            Timings {
+                gold: 1200,
+                silver: 3000,
+            },
+        );
+        assert_eq!(
+            r#"{"name":"Timed","timings":[1200,3000],"entities":[],"formatVersion":0}"#,
+            level.to_json().unwrap()
+        );
+        assert_eq!(
+            level.to_json().unwrap(),
+            Level::new(String::from("Timed"), [1200, 3000])
+                .to_json()
+                .unwrap()
+        );
+
+        let level = Level::from_json(&level.to_json().unwrap()).unwrap();
+        assert_eq!(1200, level.timings.gold);
+        assert_eq!(3000, level.timings.silver);
+    }
+
+    #[test]
+    fn format_version() {
+        let level = Level::new(String::from("Versioned"), [0, 0]);
+        assert_eq!(0, level.format_version());
+
+        let level = level.with_format_version(1);
+        assert_eq!(1, level.format_version());
+        assert_eq!(
+            r#"{"name":"Versioned","timings":[0,0],"entities":[],"formatVersion":1}"#,
+            level.to_json().unwrap()
+        );
+    }
+
+    #[test]
+    fn validate() {
+        let mut level = build_default_level();
+        assert_eq!(Ok(()), level.validate());
+
+        let player = level.remove(4);
+        assert_eq!(Err(vec![ValidationError::NoPlayer]), level.validate());
+
+        level.push(player.clone());
+        level.push(player);
+        assert_eq!(
+            Err(vec![
+                ValidationError::MultiplePlayers { count: 2 },
+                ValidationError::DuplicatePosition {
+                    first: 5,
+                    second: 6
+                },
+            ]),
+            level.validate()
+        );
+
+        let mut level = Level::new(String::from("Broken"), [0, 0]);
+        level.push(Entity::Paint {
+            fill_color: Color::BLACK,
+            opacity: 1.5,
+            vertices: vec![],
+            extra: serde_json::Map::new(),
+            note: None,
+        });
+        assert_eq!(
+            Err(vec![
+                ValidationError::NoPlayer,
+                ValidationError::NoEndpoint,
+                ValidationError::InvalidOpacity {
+                    index: 0,
+                    opacity: 1.5
+                },
+            ]),
+            level.validate()
+        );
+    }
+
+    #[test]
+    fn ammo_to_seq() {
+        for seq in ["bbeg", "g", "", "eeb"] {
+            assert_eq!(
+                Some(seq),
+                Ammo::finite_seq(seq).unwrap().to_seq().as_deref()
+            );
+        }
+        assert_eq!(
+            Some("bge"),
+            Ammo::finite_seq("BGE").unwrap().to_seq().as_deref()
+        );
+        assert_eq!(None, Ammo::Infinite(AmmoType::Bomb).to_seq());
+    }
+
+    #[test]
+    fn ammo_run_length() {
+        assert_eq!(
+            Some("bbbgge"),
+            Ammo::finite_seq("3b2ge").unwrap().to_seq().as_deref()
+        );
+        assert_eq!(
+            Some("bbeg"),
+            Ammo::finite_seq("bbeg").unwrap().to_seq().as_deref()
+        );
+        assert_eq!(
+            Some("g".repeat(12)),
+            Ammo::finite_seq("12g").unwrap().to_seq()
+        );
+        assert_eq!(
+            Some("g"),
+            Ammo::finite_seq("0bg").unwrap().to_seq().as_deref()
+        );
+
+        assert_eq!(
+            AmmoParseError::DanglingCount { index: 2 },
+            Ammo::finite_seq("bb31").unwrap_err()
+        );
+        assert_eq!(
+            AmmoParseError::UnknownChar { ch: 'x', index: 1 },
+            Ammo::finite_seq("3x").unwrap_err()
+        );
+        assert_eq!(
+            AmmoParseError::CountTooLarge { index: 1 },
+            Ammo::finite_seq("b99999999999999999999b").unwrap_err()
+        );
+    }
+
+    #[test]
+    fn ammo_finite() {
+        let ammo = Ammo::finite(vec![
+            AmmoType::Bomb,
+            AmmoType::Bomb,
+            AmmoType::Empty,
+            AmmoType::Grenade,
+        ]);
+        assert_eq!(Ammo::finite_seq("bbeg").unwrap(), ammo);
+        assert_eq!(Some("bbeg"), ammo.to_seq().as_deref());
+    }
+
+    #[test]
+    fn ammo_type_char() {
+        for (ch, ammo) in [
+            ('b', AmmoType::Bomb),
+            ('g', AmmoType::Grenade),
+            ('e', AmmoType::Empty),
+        ] {
+            assert_eq!(ch, ammo.to_char());
+            assert_eq!(ch, AmmoType::from_char(ch).unwrap().to_char());
+            assert_eq!(
+                ch,
+                AmmoType::from_char(ch.to_ascii_uppercase())
+                    .unwrap()
+                    .to_char()
+            );
+        }
+        assert_eq!(
+            AmmoParseError::UnknownChar { ch: 'q', index: 0 },
+            AmmoType::from_char('q').unwrap_err()
+        );
+    }
+
+    #[test]
+    fn paint_builder() {
+        let vertices = vec![
+            Point::new(0.0, 0.0),
+            Point::new(0.0, 1.0),
+            Point::new(1.0, 0.0),
+        ];
+        let paint = Entity::new_paint(vertices.clone());
+        assert_eq!(
+            r#"{"type":"paint","params":{"fillColor":16777215,"opacity":1.0,"vertices":[{"x":0.0,"y":0.0},{"x":0.0,"y":1.0},{"x":1.0,"y":0.0}]}}"#,
+            serde_json::to_string(&paint).unwrap()
+        );
+
+        let paint = paint
+            .with_color(Color::from_hex("#9a9996").unwrap())
+            .with_opacity(0.94);
+        assert_eq!(
+            r#"{"type":"paint","params":{"fillColor":10131862,"opacity":0.94,"vertices":[{"x":0.0,"y":0.0},{"x":0.0,"y":1.0},{"x":1.0,"y":0.0}]}}"#,
+            serde_json::to_string(&paint).unwrap()
+        );
+
+        let Entity::Paint { fill_color, .. } = Entity::new_paint(vertices).with_color(0xff8800)
+        else {
+            panic!("expected paint");
+        };
+        assert_eq!(Color::rgb(0xff, 0x88, 0x00), fill_color);
+    }
+
+    #[test]
+    fn entity_fill_color() {
+        let text = Entity::new_text(Point::ZERO, "hi").with_color(Color::rgb(0x12, 0x34, 0x56));
+        assert_eq!(Some(Color::rgb(0x12, 0x34, 0x56)), text.fill_color());
+        assert_eq!(
+            Some(Color::WHITE),
+            Entity::new_paint(vec![Point::ZERO]).fill_color()
+        );
+        assert_eq!(
+            None,
+            Entity::new_pickup(Point::ZERO, AmmoType::Bomb).fill_color()
+        );
+    }
+
+    #[test]
+    fn text_builder() {
+        let text = Entity::new_text(Point::new(10.0, 20.0), "Hello")
+            .with_translation("fr", "Bonjour")
+            .with_align(TextAlign::Right)
+            .with_anchor(Point::new(1.0, 0.5))
+            .with_color(Color::BLACK)
+            .with_opacity(0.5);
+
+        let json = serde_json::to_value(&text).unwrap();
+        assert_eq!(
+            serde_json::json!({"en": "Hello", "fr": "Bonjour"}),
+            json["params"]["copy"]
+        );
+        assert_eq!("right", json["params"]["align"]);
+        assert_eq!(
+            serde_json::json!({"x": 1.0, "y": 0.5}),
+            json["params"]["anchor"]
+        );
+        assert_eq!(0, json["params"]["fillColor"]);
+        assert_eq!(0.5, json["params"]["opacity"]);
+    }
+
+    #[test]
+    fn add_translation() {
+        let mut text = Entity::new_text(Point::default(), "Hello");
+        assert_eq!(Ok(()), text.add_translation("es", "Hola"));
+        let Entity::Text { text: copy, .. } = &text else {
+            panic!("expected text");
+        };
+        assert_eq!(Some("Hola"), copy.get("es").map(String::as_str));
+        assert_eq!(Some("Hello"), copy.get("en").map(String::as_str));
+
+        let mut door = Entity::Door {
+            is_static: true,
+            angle: 0,
+            x: 0.0,
+            y: 0.0,
+            right_facing: true,
+            extra: serde_json::Map::new(),
+            note: None,
+        };
+        let err = door.add_translation("es", "Hola").unwrap_err();
+        assert_eq!(WrongVariantError { expected: "text" }, err);
+        assert_eq!("expected a text entity", err.to_string());
+    }
+
+    #[test]
+    fn text_align_default() {
+        assert_eq!(TextAlign::Left, TextAlign::default());
+        let Entity::Text { align, .. } = Entity::new_text(Point::default(), "Hi") else {
+            panic!("expected text");
+        };
+        assert_eq!(TextAlign::default(), align);
+    }
+
+    #[test]
+    fn entity_angle() {
+        let mut door = Entity::Door {
+            is_static: true,
+            angle: 0,
+            x: 0.0,
+            y: 0.0,
+            right_facing: true,
+            extra: serde_json::Map::new(),
+            note: None,
+        };
+        assert_eq!(Some(0), door.angle());
+        door.set_angle(90);
+        assert_eq!(Some(90), door.angle());
+
+        let mut paint = Entity::new_paint(vec![]);
+        paint.set_angle(90);
+        assert_eq!(None, paint.angle());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn count_by_type() {
+        assert_eq!(
+            HashMap::from([
+                ("text", 1),
+                ("normal", 2),
+                ("ice", 1),
+                ("endpoint", 1),
+                ("player", 1),
+            ]),
+            build_default_level().count_by_type()
+        );
+    }
+
+    #[test]
+    fn type_name() {
+        let level = Level::from_json(include_str!("../all.json")).unwrap();
+        let names: Vec<_> = level.iter().map(Entity::type_name).collect();
+        assert_eq!(
+            vec![
+                "player",
+                "endpoint",
+                "text",
+                "paint",
+                "normal",
+                "ice",
+                "breakable",
+                "deadly",
+                "bouncy",
+                "normal",
+                "ice",
+                "breakable",
+                "deadly",
+                "bouncy",
+            ],
+            names
+        );
+
+        // Names match the tag serde writes
+        for entity in &level {
+            let json = serde_json::to_value(entity).unwrap();
+            assert_eq!(entity.type_name(), json["type"]);
+        }
+    }
+
+    #[test]
+    fn level_retain() {
+        let mut level = Level::from_json(include_str!("../all.json")).unwrap();
+        assert_eq!(2, level.filtered(|e| e.type_name() == "deadly").len());
+
+        level.retain(|e| e.type_name() != "deadly");
+        assert_eq!(12, level.len());
+        assert!(level.filtered(|e| e.type_name() == "deadly").is_empty());
+    }
+
+    #[test]
+    fn level_mirror() {
+        let mut level = Level::new(String::from("Mirrored"), [0, 0]);
+        level.push(Entity::Door {
+            is_static: true,
+            angle: 30,
+            x: 10.0,
+            y: 5.0,
+            right_facing: true,
+            extra: serde_json::Map::new(),
+            note: None,
+        });
+        level.push(Entity::new_normal(
+            Shape::Polygon {
+                vertices: vec![
+                    Point::new(0.0, 0.0),
+                    Point::new(0.0, 10.0),
+                    Point::new(20.0, 0.0),
+                ],
+            },
+            true,
+        ));
+
+        level.mirror_horizontal(0.0);
+        assert!(matches!(
+            level[0],
+            Entity::Door {
+                x: -10.0,
+                y: 5.0,
+                angle: 330,
+                right_facing: false,
+                ..
+            }
+        ));
+        let Entity::Normal {
+            shape: Shape::Polygon { vertices },
+            ..
+        } = &level[1]
+        else {
+            panic!("expected polygon");
+        };
+        assert_eq!(
+            &vec![
+                Point::new(-20.0, 0.0),
+                Point::new(0.0, 10.0),
+                Point::new(0.0, 0.0),
+            ],
+            vertices
+        );
+
+        level.mirror_vertical(5.0);
+        assert!(matches!(
+            level[0],
+            Entity::Door {
+                x: -10.0,
+                y: 5.0,
+                angle: 210,
+                right_facing: true,
+                ..
+            }
+        ));
+        let Entity::Normal {
+            shape: Shape::Polygon { vertices },
+            ..
+        } = &level[1]
+        else {
+            panic!("expected polygon");
+        };
+        assert_eq!(
+            &vec![
+                Point::new(0.0, 10.0),
+                Point::new(0.0, 0.0),
+                Point::new(-20.0, 10.0),
+            ],
+            vertices
+        );
+    }
+
+    #[test]
+    fn entity_mirror_extreme_angle() {
+        let mut level = Level::from_json(
+            r#"{"name":"Extreme","timings":[0,0],"entities":[{"type":"endpoint","params":{"isStatic":true,"angle":-2147483648,"x":0.0,"y":0.0,"rightFacing":true}}],"formatVersion":0}"#,
+        )
+        .unwrap();
+        // i32::MIN is 232 degrees past a whole number of turns
+        level.mirror_horizontal(0.0);
+        assert_eq!(Some(128), level[0].angle());
+        level[0].set_angle(i32::MIN);
+        level.mirror_vertical(0.0);
+        assert_eq!(Some(308), level[0].angle());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn level_rotate_all() {
+        use core::f32::consts::FRAC_PI_2;
+
+        let mut level = Level::new(String::from("Tilted"), [0, 0]);
+        level.push(Entity::Player {
+            is_static: false,
+            angle: 10,
+            x: 10.0,
+            y: 0.0,
+            ammo: Ammo::Infinite(AmmoType::Bomb),
+            extra: serde_json::Map::new(),
+            note: None,
+        });
+        level.push(Entity::new_normal(
+            Shape::rectangle_corners(Point::new(10.0, 0.0), Point::new(20.0, 10.0)),
+            true,
+        ));
+        level.push(Entity::new_bouncy(
+            Shape::Circle {
+                x: 0.0,
+                y: 10.0,
+                radius: 3.0,
+            },
+            true,
+        ));
+
+        level.rotate_all(FRAC_PI_2, Point::new(0.0, 0.0));
+
+        assert_eq!(Some(100), level[0].angle());
+        assert!(
+            level[0]
+                .position()
+                .unwrap()
+                .distance(&Point::new(0.0, 10.0))
+                < 1e-5
+        );
+
+        let Entity::Normal {
+            shape: Shape::Polygon { vertices },
+            ..
+        } = &level[1]
+        else {
+            panic!("expected polygon");
+        };
+        assert!(vertices[2].distance(&Point::new(-10.0, 20.0)) < 1e-5);
+
+        let Entity::Bouncy {
+            shape: Shape::Circle { x, y, radius },
+            ..
+        } = level[2]
+        else {
+            panic!("expected circle");
+        };
+        assert!(Point::new(x, y).distance(&Point::new(-10.0, 0.0)) < 1e-5);
+        assert_eq!(3.0, radius);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn entity_rotate_wraps_angle() {
+        use core::f32::consts::FRAC_PI_2;
+
+        let mut boost = Entity::new_boost(Point::ZERO, 30, 1.0);
+        for _ in 0..8 {
+            boost.rotate(FRAC_PI_2, Point::ZERO);
+        }
+        assert_eq!(Some(30), boost.angle());
+
+        boost.set_angle(i32::MAX);
+        boost.rotate(FRAC_PI_2, Point::ZERO);
+        // i32::MAX is 127 degrees past a whole number of turns
+        assert_eq!(Some(217), boost.angle());
+    }
+
+    #[test]
+    fn level_merge() {
+        let mut base = Level::new(String::from("Base"), [10, 20]);
+        base.push(Entity::new_text(Point::new(0.0, 0.0), "Start"));
+        base.push(Entity::new_normal(
+            Shape::rectangle(Point::new(0.0, 0.0), 2.0, 2.0),
+            true,
+        ));
+
+        let mut chunk = Level::new(String::from("Chunk"), [1, 2]);
+        chunk.push(Entity::new_deadly(
+            Shape::rectangle(Point::new(0.0, 0.0), 2.0, 2.0),
+            true,
+        ));
+        chunk.push(Entity::new_text(Point::new(1.0, 1.0), "Spikes"));
+
+        let merged = base.merged_with(&chunk, Point::new(100.0, 0.0));
+        assert_eq!("Base", merged.name);
+        assert_eq!(Timings::from([10, 20]), merged.timings);
+        assert_eq!(4, merged.len());
+        assert_eq!(Some(Point::new(100.0, 0.0)), merged[2].position());
+        assert_eq!(Some(Point::new(101.0, 1.0)), merged[3].position());
+        assert_eq!(2, base.len());
+
+        base.append(chunk);
+        assert_eq!("Base", base.name);
+        assert_eq!(4, base.len());
+        assert_eq!(Some(Point::new(0.0, 0.0)), base[2].position());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn shape_to_polygon() {
+        let center = Point::new(5.0, 5.0);
+        let circle = Shape::Circle {
+            x: center.x,
+            y: center.y,
+            radius: 10.0,
+        };
+        let Shape::Polygon { vertices } = circle.to_polygon(16) else {
+            panic!("expected polygon");
+        };
+        assert_eq!(16, vertices.len());
+        for vertex in vertices {
+            assert!((vertex.distance(&center) - 10.0).abs() < 1e-4);
+        }
+
+        let square = Shape::rectangle(center, 1.0, 1.0);
+        let Shape::Polygon { vertices } = square.to_polygon(16) else {
+            panic!("expected polygon");
+        };
+        assert_eq!(4, vertices.len());
+        assert_eq!(square, square.to_polygon(0));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    #[should_panic(expected = "at least 3 segments")]
+    fn shape_to_polygon_too_few_segments() {
+        Shape::Circle {
+            x: 0.0,
+            y: 0.0,
+            radius: 1.0,
+        }
+        .to_polygon(2);
+    }
+
+    #[test]
+    fn shape_winding() {
+        let ccw = Shape::rectangle_corners(Point::new(0.0, 0.0), Point::new(1.0, 1.0));
+        assert_eq!(Some(true), ccw.is_ccw());
+
+        let mut cw = Shape::Polygon {
+            vertices: vec![
+                Point::new(0.0, 0.0),
+                Point::new(1.0, 0.0),
+                Point::new(1.0, 1.0),
+                Point::new(0.0, 1.0),
+            ],
+        };
+        assert_eq!(Some(false), cw.is_ccw());
+        cw.ensure_ccw();
+        assert_eq!(Some(true), cw.is_ccw());
+        let Shape::Polygon { vertices } = &cw else {
+            panic!("expected polygon");
+        };
+        assert_eq!(Point::new(0.0, 1.0), vertices[0]);
+
+        let mut already = ccw.clone();
+        already.ensure_ccw();
+        let (Shape::Polygon { vertices: a }, Shape::Polygon { vertices: b }) = (&ccw, &already)
+        else {
+            panic!("expected polygons");
+        };
+        assert_eq!(a, b);
+
+        let circle = Shape::Circle {
+            x: 0.0,
+            y: 0.0,
+            radius: 1.0,
+        };
+        assert_eq!(None, circle.is_ccw());
+    }
+
+    #[test]
+    fn shape_is_simple() {
+        let square = Shape::rectangle(Point::new(0.0, 0.0), 2.0, 2.0);
+        assert!(square.is_simple());
+
+        let bowtie = Shape::Polygon {
+            vertices: vec![
+                Point::new(0.0, 0.0),
+                Point::new(1.0, 1.0),
+                Point::new(1.0, 0.0),
+                Point::new(0.0, 1.0),
+            ],
+        };
+        assert!(!bowtie.is_simple());
+
+        let concave = Shape::Polygon {
+            vertices: vec![
+                Point::new(0.0, 0.0),
+                Point::new(0.0, 10.0),
+                Point::new(10.0, 10.0),
+                Point::new(10.0, 8.0),
+                Point::new(2.0, 8.0),
+                Point::new(2.0, 0.0),
+            ],
+        };
+        assert!(concave.is_simple());
+
+        let circle = Shape::Circle {
+            x: 0.0,
+            y: 0.0,
+            radius: 1.0,
+        };
+        assert!(circle.is_simple());
+    }
+
+    #[test]
+    fn convex_hull_square() {
+        let points = [
+            Point::new(0.0, 0.0),
+            Point::new(5.0, 5.0),
+            Point::new(10.0, 0.0),
+            Point::new(10.0, 10.0),
+            Point::new(2.0, 7.0),
+            Point::new(0.0, 10.0),
+            Point::new(5.0, 0.0),
+            Point::new(10.0, 10.0),
+        ];
+        let hull = convex_hull(&points);
+        assert_eq!(Some(true), hull.is_ccw());
+        let Shape::Polygon { vertices } = hull else {
+            panic!("expected polygon");
+        };
+        assert_eq!(4, vertices.len());
+        for corner in [
+            Point::new(0.0, 0.0),
+            Point::new(10.0, 0.0),
+            Point::new(10.0, 10.0),
+            Point::new(0.0, 10.0),
+        ] {
+            assert!(vertices.contains(&corner));
+        }
+
+        let Shape::Polygon { vertices } = convex_hull(&[
+            Point::new(0.0, 0.0),
+            Point::new(1.0, 1.0),
+            Point::new(2.0, 2.0),
+        ]) else {
+            panic!("expected polygon");
+        };
+        assert_eq!(2, vertices.len());
+
+        let Shape::Polygon { vertices } = convex_hull(&[Point::new(1.0, 1.0); 3]) else {
+            panic!("expected polygon");
+        };
+        assert_eq!(vec![Point::new(1.0, 1.0)], vertices);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn shape_is_convex() {
+        let pentagon = Shape::regular_polygon(Point::new(0.0, 0.0), 5.0, 5);
+        assert_eq!(Some(true), pentagon.is_convex());
+
+        let with_collinear = Shape::Polygon {
+            vertices: vec![
+                Point::new(0.0, 0.0),
+                Point::new(0.0, 5.0),
+                Point::new(0.0, 10.0),
+                Point::new(10.0, 10.0),
+                Point::new(10.0, 0.0),
+            ],
+        };
+        assert_eq!(Some(true), with_collinear.is_convex());
+
+        let l_shape = Shape::Polygon {
+            vertices: vec![
+                Point::new(0.0, 0.0),
+                Point::new(0.0, 10.0),
+                Point::new(10.0, 10.0),
+                Point::new(10.0, 8.0),
+                Point::new(2.0, 8.0),
+                Point::new(2.0, 0.0),
+            ],
+        };
+        assert_eq!(Some(false), l_shape.is_convex());
+
+        let Shape::Polygon { vertices } = Shape::regular_polygon(Point::new(0.0, 0.0), 5.0, 5)
+        else {
+            panic!("expected polygon");
+        };
+        let star = Shape::Polygon {
+            vertices: (0..5).map(|i| vertices[i * 2 % 5]).collect(),
+        };
+        assert_eq!(Some(false), star.is_convex());
+
+        let circle = Shape::Circle {
+            x: 0.0,
+            y: 0.0,
+            radius: 1.0,
+        };
+        assert_eq!(None, circle.is_convex());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn approx_eq() {
+        let a = Point::new(1.0, 2.0);
+        assert!(a.approx_eq(&Point::new(1.0005, 1.9995), 1e-3));
+        assert!(!a.approx_eq(&Point::new(1.01, 2.0), 1e-3));
+
+        let square = Shape::rectangle(Point::new(0.0, 0.0), 2.0, 2.0);
+        let mut rotated = square.clone();
+        rotated.rotate(core::f32::consts::TAU, Point::new(0.0, 0.0));
+        assert!(square.approx_eq(&rotated, 1e-5));
+        assert!(!square.approx_eq(&Shape::rectangle(Point::new(0.1, 0.0), 2.0, 2.0), 1e-5));
+
+        let circle = Shape::Circle {
+            x: 0.0,
+            y: 0.0,
+            radius: 1.0,
+        };
+        let bigger = Shape::Circle {
+            x: 0.0,
+            y: 0.0,
+            radius: 1.1,
+        };
+        assert!(circle.approx_eq(&bigger, 0.2));
+        assert!(!circle.approx_eq(&bigger, 0.01));
+        assert!(!circle.approx_eq(&square, 10.0));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn lerp() {
+        let a = Point::new(0.0, 10.0);
+        let b = Point::new(10.0, 20.0);
+        assert_eq!(a, a.lerp(&b, 0.0));
+        assert_eq!(Point::new(5.0, 15.0), a.lerp(&b, 0.5));
+        assert_eq!(b, a.lerp(&b, 1.0));
+        assert_eq!(Point::new(20.0, 30.0), a.lerp(&b, 2.0));
+
+        let small = Shape::rectangle(Point::new(0.0, 0.0), 2.0, 2.0);
+        let big = Shape::rectangle(Point::new(0.0, 0.0), 4.0, 4.0);
+        let mid = small.lerp(&big, 0.5).unwrap();
+        assert!(mid.approx_eq(&Shape::rectangle(Point::new(0.0, 0.0), 3.0, 3.0), 1e-6));
+
+        let triangle = Shape::regular_polygon(Point::new(0.0, 0.0), 1.0, 3);
+        assert!(small.lerp(&triangle, 0.5).is_none());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn point_length() {
+        let v = Point::new(3.0, 4.0);
+        assert_eq!(5.0, v.length());
+        assert_eq!(25.0, v.length_squared());
+        assert!((v.normalize().length() - 1.0).abs() < 1e-6);
+        assert_eq!(Point::new(0.6, 0.8), v.normalize());
+        assert_eq!(Point::default(), Point::default().normalize());
+        assert_eq!(Point::new(1.5, 2.0), v.midpoint(&Point::default()));
+    }
+
+    #[test]
+    fn point_consts() {
+        assert_eq!(Point::default(), Point::ZERO);
+        assert_eq!(Point::new(1.0, 1.0), Point::ONE);
+        assert_eq!(Point::new(3.0, 3.0), Point::ONE * 3.0 + Point::ZERO);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn write_to() {
+        let level = build_default_level();
+
+        let mut buf = Vec::new();
+        level.write_to(&mut buf).unwrap();
+        assert_eq!(level.to_json().unwrap().as_bytes(), buf);
+
+        let mut buf = Vec::new();
+        level.write_to_pretty(&mut buf).unwrap();
+        assert_eq!(level.to_json_pretty().unwrap().as_bytes(), buf);
+    }
+
+    #[test]
+    fn level_builder() {
+        let mut entities = build_default_level().entities.into_iter();
+        let text = entities.next().unwrap();
+
+        let level = Level::builder()
+            .name("My level")
+            .timings([0, 0])
+            .entity(text)
+            .entities(entities)
+            .build();
+        assert_eq!(DEFAULT_LEVEL, level.to_json().unwrap());
+
+        let empty = LevelBuilder::default().build();
+        assert_eq!(
+            r#"{"name":"","timings":[0,0],"entities":[],"formatVersion":0}"#,
+            empty.to_json().unwrap()
+        );
+    }
+
+    #[test]
+    fn grid() {
+        assert!(grid_tile(1, 2, 60.0).approx_eq(
+            &Shape::rectangle_corners(Point::new(60.0, 120.0), Point::new(120.0, 180.0)),
+            0.0
+        ));
+
+        let tiles = grid_tiles(&[(0, 0), (1, 0), (0, 1), (1, 1)], 60.0, Entity::new_normal);
+        assert_eq!(4, tiles.len());
+        assert!(tiles.iter().all(|t| matches!(
+            t,
+            Entity::Normal {
+                is_static: true,
+                ..
+            }
+        )));
+
+        let level = Level::with_entities(String::new(), [0, 0], tiles);
+        assert_eq!(
+            Some((Point::new(0.0, 0.0), Point::new(120.0, 120.0))),
+            level.bounding_box()
+        );
+    }
+
+    #[test]
+    fn shape_spike_strip() {
+        let spikes = Shape::spike_strip(Point::new(0.0, 100.0), 3, 20.0, 30.0);
+        assert_eq!(Some(true), spikes.is_ccw());
+        assert!(spikes.is_simple());
+        assert!(Shape::spike_strip(Point::ZERO, 1, 20.0, 30.0).is_simple());
+        assert_eq!(
+            (Point::new(0.0, 70.0), Point::new(60.0, 100.0)),
+            spikes.bounding_box()
+        );
+        let Shape::Polygon { vertices } = spikes else {
+            panic!("expected polygon");
+        };
+        assert_eq!(7, vertices.len());
+        let peaks: Vec<_> = vertices.iter().filter(|v| v.y == 70.0).collect();
+        assert_eq!(
+            vec![
+                &Point::new(50.0, 70.0),
+                &Point::new(30.0, 70.0),
+                &Point::new(10.0, 70.0),
+            ],
+            peaks
+        );
+        let valleys: Vec<_> = vertices.iter().filter(|v| v.y == 97.0).collect();
+        assert_eq!(
+            vec![&Point::new(40.0, 97.0), &Point::new(20.0, 97.0)],
+            valleys
+        );
+    }
+
+    #[test]
+    fn level_extra_keys() {
+        let json = r#"{"name":"Extra","timings":[0,0],"entities":[],"formatVersion":0,"background":{"color":123}}"#;
+        let level = Level::from_json(json).unwrap();
+        assert_eq!(
+            Some(&serde_json::json!({"color": 123})),
+            level.extra.get("background")
+        );
+        assert_eq!(json, level.to_json().unwrap());
+
+        assert!(build_default_level().extra.is_empty());
+    }
+
+    #[test]
+    fn entity_extra_params() {
+        let json = r#"{"type":"normal","params":{"isStatic":true,"vertices":[{"x":0.0,"y":0.0}],"foo":1}}"#;
+        let entity: Entity = serde_json::from_str(json).unwrap();
+        let Entity::Normal { shape, extra, .. } = &entity else {
+            panic!("expected normal");
+        };
+        assert!(matches!(shape, Shape::Polygon { .. }));
+        assert_eq!(Some(&serde_json::json!(1)), extra.get("foo"));
+        assert_eq!(1, extra.len());
+        assert_eq!(json, serde_json::to_string(&entity).unwrap());
+
+        let json = r#"{"type":"player","params":{"isStatic":false,"angle":0,"x":0.0,"y":0.0,"infiniteAmmo":"bullet","skin":"red"}}"#;
+        let entity: Entity = serde_json::from_str(json).unwrap();
+        assert_eq!(json, serde_json::to_string(&entity).unwrap());
+
+        // Nothing extra is written for entities without unknown params
+        assert_eq!(
+            DEFAULT_LEVEL,
+            Level::from_json(DEFAULT_LEVEL).unwrap().to_json().unwrap()
+        );
+
+        assert!(serde_json::from_str::<Entity>(
+            r#"{"type":"ice","params":{"isStatic":true,"x":1.0}}"#
+        )
+        .is_err());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn group() {
+        let mut group = Group::new(grid_tiles(
+            &[(0, 0), (1, 1), (2, 2)],
+            10.0,
+            Entity::new_normal,
+        ));
+        group.scale(2.0, Point::ZERO);
+        group.translate(Point::new(100.0, 0.0));
+        group.rotate(core::f32::consts::PI, Point::new(100.0, 0.0));
+
+        let mut level = Level::new(String::from("Stairs"), [0, 0]);
+        level.extend(group.into_entities());
+        assert_eq!(3, level.len());
+        let (min, max) = level.bounding_box().unwrap();
+        assert!(min.approx_eq(&Point::new(40.0, -60.0), 1e-4));
+        assert!(max.approx_eq(&Point::new(100.0, 0.0), 1e-4));
+    }
+
+    #[test]
+    fn level_eq() {
+        assert_eq!(build_default_level(), build_default_level());
+
+        let mut moved = build_default_level();
+        moved[3].translate(Point::new(1.0, 0.0));
+        assert_ne!(build_default_level(), moved);
+
+        let mut renamed = build_default_level();
+        renamed.name = String::from("Other");
+        assert_ne!(build_default_level(), renamed);
+    }
+
+    #[test]
+    fn level_entities_at() {
+        let mut level = Level::new(String::from("Stack"), [0, 0]);
+        level.push(Entity::new_normal(
+            Shape::rectangle(Point::new(0.0, 0.0), 100.0, 100.0),
+            true,
+        ));
+        level.push(Entity::new_paint(vec![
+            Point::new(0.0, 0.0),
+            Point::new(0.0, 50.0),
+            Point::new(50.0, 50.0),
+            Point::new(50.0, 0.0),
+        ]));
+        level.push(Entity::new_text(Point::new(40.0, 40.0), "Here"));
+        level.push(Entity::new_ice(
+            Shape::Circle {
+                x: 200.0,
+                y: 200.0,
+                radius: 10.0,
+            },
+            true,
+        ));
+
+        assert_eq!(vec![0, 1, 2], level.entities_at(&Point::new(20.0, 20.0)));
+        assert_eq!(vec![0], level.entities_at(&Point::new(-20.0, 20.0)));
+        assert_eq!(vec![2], level.entities_at(&Point::new(65.0, 65.0)));
+        assert_eq!(vec![3], level.entities_at(&Point::new(205.0, 200.0)));
+        assert!(level.entities_at(&Point::new(500.0, 500.0)).is_empty());
+    }
+
+    #[test]
+    fn validate_player_in_deadly() {
+        let mut level = build_default_level();
+        level.push(Entity::new_deadly(
+            Shape::rectangle(Point::new(-60.0, 620.0), 40.0, 40.0),
+            true,
+        ));
+        assert_eq!(
+            Err(vec![
+                ValidationError::PlayerInDeadly {
+                    player: 4,
+                    deadly: 6
+                },
+                ValidationError::DuplicatePosition {
+                    first: 4,
+                    second: 6
+                },
+            ]),
+            level.validate()
+        );
+
+        level[6].translate(Point::new(0.0, 10.0));
+        assert_eq!(
+            Err(vec![ValidationError::PlayerInDeadly {
+                player: 4,
+                deadly: 6
+            }]),
+            level.validate()
+        );
+
+        level[6].translate(Point::new(100.0, 0.0));
+        assert_eq!(Ok(()), level.validate());
+    }
+
+    #[test]
+    fn text_copy_order_is_deterministic() {
+        let mut text = Entity::new_text(Point::default(), "Hello");
+        text.add_translation("fr", "Bonjour").unwrap();
+        text.add_translation("de", "Hallo").unwrap();
+        let json = serde_json::to_string(&text).unwrap();
+        assert!(json.contains(r#""copy":{"de":"Hallo","en":"Hello","fr":"Bonjour"}"#));
+
+        let mut reordered = Entity::new_text(Point::default(), "Hello");
+        reordered.add_translation("de", "Hallo").unwrap();
+        reordered.add_translation("fr", "Bonjour").unwrap();
+        assert_eq!(json, serde_json::to_string(&reordered).unwrap());
+    }
+
+    #[test]
+    fn entity_color_and_opacity_are_clamped() {
+        let text = Entity::new_text(Point::default(), "Hi")
+            .with_opacity(1.5)
+            .with_color(0x1FFFFFF);
+        let Entity::Text {
+            fill_color,
+            opacity,
+            ..
+        } = text
+        else {
+            panic!("expected a text entity");
+        };
+        assert_eq!(Color::WHITE, fill_color);
+        assert_eq!(1.0, opacity);
+
+        let paint = Entity::new_paint(vec![]).with_opacity(-0.5).with_color(-1);
+        let Entity::Paint {
+            fill_color,
+            opacity,
+            ..
+        } = paint
+        else {
+            panic!("expected a paint entity");
+        };
+        assert_eq!(Color::WHITE, fill_color);
+        assert_eq!(0.0, opacity);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn level_to_json_compact_numbers() {
+        let level = Level::with_entities(
+            String::from("Compact"),
+            [5, 10],
+            [Entity::new_normal(
+                Shape::rectangle_corners(Point::new(0.0, -20.0), Point::new(400.0, 20.5)),
+                true,
+            )],
+        );
+        assert_eq!(
+            r#"{"name":"Compact","timings":[5,10],"entities":[{"type":"normal","params":{"isStatic":true,"vertices":[{"x":0,"y":-20},{"x":0,"y":20.5},{"x":400,"y":20.5},{"x":400,"y":-20}]}}],"formatVersion":0}"#,
+            level.to_json_compact_numbers().unwrap()
+        );
+        assert_eq!(
+            level,
+            Level::from_json(&level.to_json_compact_numbers().unwrap()).unwrap()
+        );
+    }
+
+    #[test]
+    fn entity_boost_serialization() {
+        let boost = Entity::new_boost(Point::new(100.0, 200.0), 90, 1.5);
+        let json = serde_json::to_value(&boost).unwrap();
+        assert_eq!(
+            serde_json::json!({
+                "type": "boost",
+                "params": {"isStatic": true, "angle": 90, "x": 100.0, "y": 200.0, "strength": 1.5}
+            }),
+            json
+        );
+        assert_eq!("boost", boost.type_name());
+        assert_eq!(boost, serde_json::from_value(json).unwrap());
+    }
+
+    #[test]
+    fn entity_pickup_serialization() {
+        let mut pickup = Entity::new_pickup(Point::new(10.0, 20.0), AmmoType::Grenade);
+        let json = serde_json::to_value(&pickup).unwrap();
+        assert_eq!(
+            serde_json::json!({
+                "type": "pickup",
+                "params": {"x": 10.0, "y": 20.0, "ammoType": "grenade"}
+            }),
+            json
+        );
+        assert_eq!(pickup, serde_json::from_value(json).unwrap());
+
+        pickup.mirror_horizontal(0.0);
+        assert_eq!(Some(Point::new(-10.0, 20.0)), pickup.position());
+        assert_eq!(None, pickup.angle());
+    }
+
+    #[test]
+    fn entity_moving_platform() {
+        let shape = Shape::rectangle_corners(Point::new(0.0, 0.0), Point::new(2.0, 1.0));
+        let waypoints = vec![Point::new(0.0, 0.0), Point::new(100.0, 0.0)];
+        assert_eq!(
+            Err(TooFewWaypointsError { count: 1 }),
+            Entity::new_moving_platform(shape.clone(), waypoints[..1].to_vec(), 5.0)
+        );
+
+        let mut platform = Entity::new_moving_platform(shape, waypoints, 5.0).unwrap();
+        let json = serde_json::to_value(&platform).unwrap();
+        assert_eq!(
+            serde_json::json!({
+                "type": "movingPlatform",
+                "params": {
+                    "vertices": [
+                        {"x": 0.0, "y": 0.0},
+                        {"x": 0.0, "y": 1.0},
+                        {"x": 2.0, "y": 1.0},
+                        {"x": 2.0, "y": 0.0}
+                    ],
+                    "waypoints": [{"x": 0.0, "y": 0.0}, {"x": 100.0, "y": 0.0}],
+                    "speed": 5.0
+                }
+            }),
+            json
+        );
+        assert_eq!(platform, serde_json::from_value(json).unwrap());
+
+        platform.translate(Point::new(0.0, 10.0));
+        let Entity::MovingPlatform { waypoints, .. } = &mut platform else {
+            panic!("expected a moving platform");
+        };
+        assert_eq!(Point::new(100.0, 10.0), waypoints[1]);
+        waypoints.clear();
+
+        let mut level = build_default_level();
+        level.push(platform);
+        assert_eq!(
+            Err(vec![ValidationError::TooFewWaypoints {
+                index: 6,
+                count: 0
+            }]),
+            level.validate()
+        );
     }
-    /// Pushes entity onto entities vector
-    pub fn push(&mut self, entity: Entity) {
-        self.entities.push(entity);
+
+    #[test]
+    fn ammo_finite_runs() {
+        assert_eq!(
+            Ammo::finite_seq("bbg").unwrap(),
+            Ammo::finite_runs(&[(AmmoType::Bomb, 2), (AmmoType::Grenade, 1)])
+        );
+        assert_eq!(
+            Ammo::finite_seq("be").unwrap(),
+            Ammo::finite_runs(&[
+                (AmmoType::Bomb, 1),
+                (AmmoType::Grenade, 0),
+                (AmmoType::Empty, 1)
+            ])
+        );
     }
-    /// Clears all entities from entities vector
-    pub fn clear(&mut self) {
-        self.entities.clear();
+
+    #[test]
+    fn ammo_len() {
+        let finite = Ammo::finite_seq("3bg").unwrap();
+        assert_eq!(Some(4), finite.len());
+        assert!(!finite.is_infinite());
+        assert!(!finite.is_empty());
+        assert_eq!(Some(0), Ammo::finite(vec![]).len());
+        assert!(Ammo::finite(vec![]).is_empty());
+
+        let infinite = Ammo::Infinite(AmmoType::Bomb);
+        assert_eq!(None, infinite.len());
+        assert!(infinite.is_infinite());
+        assert!(!infinite.is_empty());
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use serde_json;
+    #[test]
+    fn level_scale_all() {
+        let mut level = Level::with_entities(
+            String::from("Scaled"),
+            [0, 0],
+            [
+                Entity::new_normal(
+                    Shape::rectangle_corners(Point::new(1.0, 2.0), Point::new(3.0, 4.0)),
+                    true,
+                ),
+                Entity::new_ice(
+                    Shape::Circle {
+                        x: -1.0,
+                        y: 1.0,
+                        radius: 5.0,
+                    },
+                    false,
+                ),
+                Entity::new_text(Point::new(10.0, 0.0), "Hi"),
+            ],
+        );
+        level[2].set_angle(30);
+        level.scale_all(2.0, Point::ZERO);
+
+        let Entity::Normal {
+            shape: Shape::Polygon { vertices },
+            ..
+        } = &level[0]
+        else {
+            panic!("expected a polygon");
+        };
+        assert_eq!(Point::new(6.0, 8.0), vertices[2]);
+        assert_eq!(
+            Shape::Circle {
+                x: -2.0,
+                y: 2.0,
+                radius: 10.0
+            },
+            level[1].shape().cloned().unwrap()
+        );
+        assert_eq!(Some(Point::new(20.0, 0.0)), level[2].position());
+        assert_eq!(Some(30), level[2].angle());
+    }
 
     #[test]
-    fn ammo() {
-        // Tests if infinte bombs goes correctly
+    fn level_value_round_trip() {
+        let level = build_default_level();
+        let value = level.to_value().unwrap();
         assert_eq!(
-            r#"{"type":"player","params":{"isStatic":false,"angle":0,"x":0.0,"y":0.0,"infiniteAmmo":"bullet"}}"#,
-            serde_json::to_string(&Entity::Player {
-                is_static: false,
-                angle: 0,
+            serde_json::from_str::<serde_json::Value>(DEFAULT_LEVEL).unwrap(),
+            value
+        );
+        assert_eq!(level, Level::from_value(value).unwrap());
+        assert!(Level::from_value(serde_json::json!({"name": 1})).is_err());
+    }
+
+    #[test]
+    fn shape_edges() {
+        let (a, b, c) = (
+            Point::new(0.0, 0.0),
+            Point::new(1.0, 0.0),
+            Point::new(0.0, 1.0),
+        );
+        let triangle = Shape::Polygon {
+            vertices: vec![a, b, c],
+        };
+        assert_eq!(vec![(a, b), (b, c), (c, a)], triangle.edges());
+        assert!(Shape::Circle {
+            x: 0.0,
+            y: 0.0,
+            radius: 1.0
+        }
+        .edges()
+        .is_empty());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn shape_perimeter() {
+        let square = Shape::rectangle_corners(Point::ZERO, Point::ONE);
+        assert_eq!(4.0, square.perimeter());
+        let circle = Shape::Circle {
+            x: 5.0,
+            y: 5.0,
+            radius: 1.0,
+        };
+        assert!((circle.perimeter() - 2.0 * std::f32::consts::PI).abs() < 1e-6);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn level_snap_to_grid() {
+        let mut level = Level::with_entities(
+            String::new(),
+            [0, 0],
+            [
+                Entity::new_text(Point::new(401.3, -14.9), "Hi"),
+                Entity::new_bouncy(
+                    Shape::Circle {
+                        x: 3.0,
+                        y: 27.0,
+                        radius: 12.0,
+                    },
+                    true,
+                ),
+            ],
+        );
+        level.snap_to_grid(10.0);
+        assert_eq!(Some(Point::new(400.0, -10.0)), level[0].position());
+        assert_eq!(
+            Some(&Shape::Circle {
                 x: 0.0,
-                y: 0.0,
-                ammo: Ammo::Infinite(AmmoType::Bomb)
-            })
-            .unwrap()
+                y: 30.0,
+                radius: 10.0
+            }),
+            level[1].shape()
         );
+    }
 
-        // Tests if finite seq works (and finite)
+    #[cfg(feature = "std")]
+    #[test]
+    #[should_panic(expected = "grid must be positive")]
+    fn level_snap_to_grid_zero() {
+        Level::new(String::new(), [0, 0]).snap_to_grid(0.0);
+    }
+
+    #[test]
+    fn shape_dedup_vertices() {
+        let (a, b, c) = (
+            Point::new(0.0, 0.0),
+            Point::new(1.0, 0.0),
+            Point::new(0.0, 1.0),
+        );
+        let mut shape = Shape::Polygon {
+            vertices: vec![a, b, b + Point::new(1e-6, 0.0), c, a],
+        };
+        shape.dedup_vertices();
         assert_eq!(
-            r#"{"type":"player","params":{"isStatic":false,"angle":0,"x":0.0,"y":0.0,"magazine":["grenade","empty","bullet","bullet"]}}"#,
-            serde_json::to_string(&Entity::Player {
-                is_static: false,
-                angle: 0,
+            Shape::Polygon {
+                vertices: vec![a, b, c]
+            },
+            shape
+        );
+
+        let mut circle = Shape::Circle {
+            x: 0.0,
+            y: 0.0,
+            radius: 1.0,
+        };
+        circle.dedup_vertices();
+        assert_eq!(
+            Shape::Circle {
                 x: 0.0,
                 y: 0.0,
-                ammo: Ammo::finite_seq("bbeg").unwrap()
-            })
+                radius: 1.0
+            },
+            circle
+        );
+    }
+
+    #[test]
+    fn shape_reverse_winding() {
+        let (a, b, c) = (
+            Point::new(0.0, 0.0),
+            Point::new(1.0, 0.0),
+            Point::new(0.0, 1.0),
+        );
+        let mut triangle = Shape::Polygon {
+            vertices: vec![a, b, c],
+        };
+        let was_ccw = triangle.is_ccw();
+        triangle.reverse_winding();
+        assert_eq!(
+            Shape::Polygon {
+                vertices: vec![c, b, a]
+            },
+            triangle
+        );
+        assert_eq!(was_ccw.map(|ccw| !ccw), triangle.is_ccw());
+
+        let circle = Shape::Circle {
+            x: 1.0,
+            y: 2.0,
+            radius: 3.0,
+        };
+        let mut reversed = circle.clone();
+        reversed.reverse_winding();
+        assert_eq!(circle, reversed);
+    }
+
+    #[test]
+    fn level_pack_round_trip() {
+        let pack = LevelPack::new(vec![
+            build_default_level(),
+            Level::new(String::from("Second"), [1, 2]),
+        ]);
+        let json = pack.to_json().unwrap();
+        assert_eq!(
+            format!(
+                "[{},{}]",
+                DEFAULT_LEVEL,
+                r#"{"name":"Second","timings":[1,2],"entities":[],"formatVersion":0}"#
+            ),
+            json
+        );
+        assert_eq!(pack, LevelPack::from_json(&json).unwrap());
+    }
+
+    #[test]
+    fn level_find() {
+        let mut level = build_default_level();
+        assert_eq!(Some(&level[4]), level.player());
+        assert_eq!(vec![&level[3]], level.endpoints());
+        assert_eq!(Some(&level[2]), level.find(|e| e.type_name() == "ice"));
+        assert_eq!(None, level.find(|e| e.type_name() == "deadly"));
+
+        level
+            .find_mut(|e| e.type_name() == "text")
             .unwrap()
-        )
+            .set_angle(45);
+        assert_eq!(Some(45), level[0].angle());
+
+        level.remove(4);
+        assert_eq!(None, level.player());
     }
 
     #[test]
-    fn default_level() {
-        let mut level = Level::new(String::from("My level"), [0, 0]);
-        level.push(Entity::new_text(
-            Point::new(200.0, 520.0),
-            "This is the default level!\nEdit to your liking",
+    fn entity_set_ammo() {
+        let mut level = build_default_level();
+        let grenades = Ammo::finite_seq("gg").unwrap();
+        level[4].set_ammo(grenades.clone()).unwrap();
+        assert_eq!(Some(&grenades), level[4].ammo());
+
+        assert_eq!(
+            Err(WrongVariantError { expected: "player" }),
+            level[3].set_ammo(grenades)
+        );
+        assert_eq!(None, level[3].ammo());
+    }
+
+    #[test]
+    fn level_from_json_limited() {
+        let level = Level::from_json_limited(DEFAULT_LEVEL, 6, 100).unwrap();
+        assert_eq!(build_default_level(), level);
+        assert!(Level::from_json_limited(DEFAULT_LEVEL, 7, 100).is_ok());
+
+        assert!(matches!(
+            Level::from_json_limited(DEFAULT_LEVEL, 5, 100),
+            Err(LoadError::TooManyEntities { count: 6, max: 5 })
+        ));
+        assert!(matches!(
+            Level::from_json_limited(DEFAULT_LEVEL, 6, 1),
+            Err(LoadError::TooManyVertices { max: 1, .. })
+        ));
+        assert!(matches!(
+            Level::from_json_limited("[]", 6, 100),
+            Err(LoadError::Json(_))
         ));
 
-        level.push(Entity::Normal {
-            is_static: true,
-            shape: Shape::Polygon {
-                vertices: vec![
-                    Point::new(400.0, 820.0),
-                    Point::new(400.0, 880.0),
-                    Point::new(520.0, 880.0),
-                    Point::new(520.0, 820.0),
-                ],
-            },
-        });
+        // the entities aren't valid, so the limit must be hit before parsing them
+        let oversized = format!(
+            r#"{{"name":"Big","entities":[{}],"timings":[0,0]}}"#,
+            vec!["{}"; 10_000].join(",")
+        );
+        assert!(matches!(
+            Level::from_json_limited(&oversized, 100, 100),
+            Err(LoadError::TooManyEntities {
+                count: 10_000,
+                max: 100
+            })
+        ));
+        let points = vec![r#"{"x":0,"y":0}"#; 500].join(",");
+        let oversized = format!(
+            r#"{{"entities":[{{"type":"paint","params":{{"vertices":[{}]}}}}]}}"#,
+            points
+        );
+        assert!(matches!(
+            Level::from_json_limited(&oversized, 100, 100),
+            Err(LoadError::TooManyVertices {
+                count: 500,
+                max: 100
+            })
+        ));
+        assert!(matches!(
+            Level::from_json_limited(&oversized, 100, 500),
+            Err(LoadError::Json(_))
+        ));
+    }
 
-        level.push(Entity::Ice {
-            is_static: true,
-            shape: Shape::Polygon {
-                vertices: vec![
-                    Point::new(-260.0, 580.0),
-                    Point::new(-260.0, 820.0),
-                    Point::new(400.0, 820.0),
-                    Point::new(400.0, 760.0),
-                    Point::new(160.0, 760.0),
-                    Point::new(-20.0, 640.0),
-                    Point::new(-140.0, 640.0),
-                ],
-            },
-        });
+    #[test]
+    fn level_center_of_mass() {
+        let mut level = Level::new(String::new(), [0, 0]);
+        assert_eq!(None, level.center_of_mass());
 
-        level.push(Entity::Door {
-            is_static: true,
-            angle: 0,
-            x: 550.0,
-            y: 630.0,
-            right_facing: true,
-        });
+        level.push(Entity::new_text(Point::new(500.0, 500.0), "Hi"));
+        level.push(Entity::new_normal(
+            Shape::rectangle_corners(Point::new(0.0, 0.0), Point::new(2.0, 2.0)),
+            true,
+        ));
+        level.push(Entity::new_ice(
+            Shape::rectangle_corners(Point::new(10.0, 4.0), Point::new(12.0, 6.0)),
+            true,
+        ));
+        assert_eq!(Some(Point::new(6.0, 3.0)), level.center_of_mass());
+    }
 
-        level.push(Entity::Player {
-            is_static: false,
-            angle: 0,
-            x: -60.0,
-            y: 620.0,
-            ammo: Ammo::finite_seq("beg").unwrap(),
-        });
+    #[test]
+    fn repeat_along_steps() {
+        let tile = Entity::new_normal(grid_tile(0, 0, 60.0), true);
+        let row = repeat_along(&tile, Point::new(60.0, 0.0), 4);
+        assert_eq!(
+            grid_tiles(&[(0, 0), (1, 0), (2, 0), (3, 0)], 60.0, Entity::new_normal),
+            row
+        );
+        assert!(repeat_along(&tile, Point::ONE, 0).is_empty());
+    }
 
-        level.push(Entity::Normal {
-            is_static: false,
-            shape: Shape::Polygon {
-                vertices: vec![
-                    Point::new(-236.0, 292.0),
-                    Point::new(-176.0, 292.0),
-                    Point::new(-176.0, 352.0),
-                    Point::new(-236.0, 352.0),
-                ],
+    #[test]
+    fn entity_locked_flag() {
+        let json = r#"{"type":"ice","params":{"isStatic":true,"x":0.0,"y":0.0,"radius":5.0,"locked":true}}"#;
+        let mut entity: Entity = serde_json::from_str(json).unwrap();
+        assert_eq!(Some(true), entity.locked());
+        assert_eq!(json, serde_json::to_string(&entity).unwrap());
+
+        entity.set_locked(None);
+        assert_eq!(None, entity.locked());
+        assert_eq!(
+            r#"{"type":"ice","params":{"isStatic":true,"x":0.0,"y":0.0,"radius":5.0}}"#,
+            serde_json::to_string(&entity).unwrap()
+        );
+
+        entity.set_locked(Some(false));
+        assert_eq!(Some(false), entity.locked());
+    }
+
+    #[test]
+    fn ammo_firing_order() {
+        use AmmoType::*;
+        assert_eq!(
+            Some(vec![&Bomb, &Bomb, &Empty, &Grenade]),
+            Ammo::finite_seq("bbeg").unwrap().firing_order()
+        );
+        assert_eq!(None, Ammo::Infinite(Bomb).firing_order());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn entity_angle_radians() {
+        let mut text = Entity::new_text(Point::default(), "Hi");
+        text.set_angle_radians(std::f32::consts::FRAC_PI_2);
+        assert_eq!(Some(90), text.angle());
+        assert!((text.angle_radians().unwrap() - std::f32::consts::FRAC_PI_2).abs() < 1e-6);
+
+        text.set_angle_radians(0.01);
+        assert_eq!(Some(1), text.angle());
+        assert_eq!(None, Entity::new_paint(vec![]).angle_radians());
+    }
+
+    #[test]
+    fn level_sort_by_type() {
+        let mut level = build_default_level();
+        let before = level.clone();
+        level.sort_by_type(&["normal", "text"]);
+        let names: Vec<_> = level.iter().map(Entity::type_name).collect();
+        assert_eq!(
+            vec!["normal", "normal", "ice", "endpoint", "player", "text"],
+            names
+        );
+        // the two normals keep their relative order
+        assert_eq!(before[1], level[0]);
+        assert_eq!(before[5], level[1]);
+        assert_eq!(before[0], level[5]);
+    }
+
+    #[test]
+    fn level_dedup_entities() {
+        let left = Level::with_entities(
+            String::from("Left"),
+            [0, 0],
+            grid_tiles(&[(0, 0), (1, 0)], 60.0, Entity::new_normal),
+        );
+        let right = Level::with_entities(
+            String::from("Right"),
+            [0, 0],
+            grid_tiles(&[(0, 0), (1, 0)], 60.0, Entity::new_normal),
+        );
+        let mut merged = left.merged_with(&right, Point::new(60.001, 0.0));
+        assert_eq!(4, merged.len());
+        merged.dedup_entities(0.01);
+        assert_eq!(
+            grid_tiles(&[(0, 0), (1, 0)], 60.0, Entity::new_normal),
+            merged.entities[..2]
+        );
+        assert_eq!(3, merged.len());
+
+        // same geometry but a different material isn't a duplicate
+        merged.push(Entity::new_ice(grid_tile(0, 0, 60.0), true));
+        merged.dedup_entities(0.01);
+        assert_eq!(4, merged.len());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn shape_offset() {
+        let square = Shape::rectangle_corners(Point::ZERO, Point::ONE);
+        let grown = square.offset(1.0);
+        let (min, max) = grown.bounding_box();
+        assert!(min.approx_eq(&Point::new(-1.0, -1.0), 1e-6));
+        assert!(max.approx_eq(&Point::new(2.0, 2.0), 1e-6));
+        assert!(grown.offset(-1.0).approx_eq(&square, 1e-6));
+
+        let mut reversed = square.clone();
+        reversed.reverse_winding();
+        assert!(reversed.offset(1.0).bounding_box().0.approx_eq(&min, 1e-6));
+
+        let collapsed = square.offset(-2.0);
+        assert_eq!(0.0, collapsed.area());
+        assert_eq!(Shape::from(vec![Point::new(0.5, 0.5); 4]), collapsed);
+
+        let circle = Shape::Circle {
+            x: 0.0,
+            y: 0.0,
+            radius: 2.0,
+        };
+        assert_eq!(
+            Shape::Circle {
+                x: 0.0,
+                y: 0.0,
+                radius: 0.0
             },
-        });
+            circle.offset(-3.0)
+        );
+    }
+
+    #[test]
+    fn shape_vertices_conversions() {
+        let vertices = vec![
+            Point::new(0.0, 0.0),
+            Point::new(1.0, 0.0),
+            Point::new(0.0, 1.0),
+        ];
+        let shape = Shape::from(vertices.clone());
+        assert_eq!(
+            Shape::Polygon {
+                vertices: vertices.clone()
+            },
+            shape
+        );
+        assert_eq!(Ok(vertices), Vec::<Point>::try_from(shape));
 
+        let circle = Shape::Circle {
+            x: 0.0,
+            y: 0.0,
+            radius: 1.0,
+        };
+        assert_eq!(Err(circle.clone()), Vec::<Point>::try_from(circle));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn entity_new_text_multi() {
+        let translations = HashMap::from([
+            (String::from("en"), String::from("Hello")),
+            (String::from("es"), String::from("Hola")),
+        ]);
+        let text = Entity::new_text_multi(Point::new(1.0, 2.0), translations).unwrap();
+        assert_eq!(
+            Entity::new_text(Point::new(1.0, 2.0), "Hello").with_translation("es", "Hola"),
+            text
+        );
         assert_eq!(
-            r#"{"name":"My level","timings":[0,0],"entities":[{"type":"text","params":{"angle":0,"x":200.0,"y":520.0,"copy":{"en":"This is the default level!\nEdit to your liking"},"anchor":{"x":0.5,"y":0.5},"align":"left","fillColor":16777215,"opacity":1.0}},{"type":"normal","params":{"isStatic":true,"vertices":[{"x":400.0,"y":820.0},{"x":400.0,"y":880.0},{"x":520.0,"y":880.0},{"x":520.0,"y":820.0}]}},{"type":"ice","params":{"isStatic":true,"vertices":[{"x":-260.0,"y":580.0},{"x":-260.0,"y":820.0},{"x":400.0,"y":820.0},{"x":400.0,"y":760.0},{"x":160.0,"y":760.0},{"x":-20.0,"y":640.0},{"x":-140.0,"y":640.0}]}},{"type":"endpoint","params":{"isStatic":true,"angle":0,"x":550.0,"y":630.0,"rightFacing":true}},{"type":"player","params":{"isStatic":false,"angle":0,"x":-60.0,"y":620.0,"magazine":["grenade","empty","bullet"]}},{"type":"normal","params":{"isStatic":false,"vertices":[{"x":-236.0,"y":292.0},{"x":-176.0,"y":292.0},{"x":-176.0,"y":352.0},{"x":-236.0,"y":352.0}]}}],"formatVersion":0}"#,
-            serde_json::to_string(&level).unwrap()
+            Err(NoTranslationsError),
+            Entity::new_text_multi(Point::default(), HashMap::new())
+        );
+    }
+
+    #[test]
+    fn entity_note_is_not_serialized() {
+        let mut entity = Entity::new_normal(grid_tile(0, 0, 60.0), true);
+        let json = serde_json::to_string(&entity).unwrap();
+        entity.set_note(Some(String::from("widen this gap")));
+        assert_eq!(Some("widen this gap"), entity.clone().note());
+        assert_eq!(json, serde_json::to_string(&entity).unwrap());
+
+        let loaded: Entity = serde_json::from_str(&json).unwrap();
+        assert_eq!(None, loaded.note());
+        entity.set_note(None);
+        assert_eq!(loaded, entity);
+    }
+
+    #[test]
+    fn level_endpoint_line_of_sight() {
+        let mut level = Level::new(String::from("Sight"), [0, 0]);
+        assert!(!level.endpoint_line_of_sight());
+
+        level.push(build_default_level()[4].clone());
+        level[0].set_position(Point::new(0.0, 0.0));
+        level.push(build_default_level()[3].clone());
+        level[1].set_position(Point::new(500.0, 0.0));
+        level.push(Entity::new_normal(
+            Shape::rectangle_corners(Point::new(-100.0, 50.0), Point::new(600.0, 100.0)),
+            true,
+        ));
+        assert!(level.endpoint_line_of_sight());
+
+        let wall = Shape::rectangle(Point::new(250.0, 0.0), 20.0, 200.0);
+        level.push(Entity::new_bouncy(wall.clone(), true));
+        assert!(level.endpoint_line_of_sight());
+        level.push(Entity::new_breakable(wall, true));
+        assert!(!level.endpoint_line_of_sight());
+    }
+
+    #[test]
+    fn shape_intersects_segment() {
+        let square = Shape::rectangle_corners(Point::ZERO, Point::new(10.0, 10.0));
+        let crossing = (Point::new(-5.0, 5.0), Point::new(15.0, 5.0));
+        let inside = (Point::new(2.0, 2.0), Point::new(8.0, 3.0));
+        let missing = (Point::new(-5.0, -5.0), Point::new(15.0, -1.0));
+        assert!(square.intersects_segment(&crossing.0, &crossing.1));
+        assert!(square.intersects_segment(&inside.0, &inside.1));
+        assert!(!square.intersects_segment(&missing.0, &missing.1));
+
+        let circle = Shape::Circle {
+            x: 5.0,
+            y: 5.0,
+            radius: 5.0,
+        };
+        assert!(circle.intersects_segment(&crossing.0, &crossing.1));
+        assert!(circle.intersects_segment(&inside.0, &inside.1));
+        assert!(!circle.intersects_segment(&missing.0, &missing.1));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn entity_outline() {
+        let level = build_default_level();
+        let Entity::Normal {
+            shape: Shape::Polygon { vertices },
+            ..
+        } = &level[1]
+        else {
+            panic!("expected a polygon");
+        };
+        assert_eq!(*vertices, level[1].outline());
+
+        let door = level[3].outline();
+        assert_eq!(4, door.len());
+        let center = level[3].position().unwrap();
+        let half = MARKER_SIZE / 2.0;
+        assert_eq!(center - Point::new(half, half), door[0]);
+        assert_eq!(center + Point::new(half, half), door[2]);
+
+        let circle = Entity::new_ice(
+            Shape::Circle {
+                x: 0.0,
+                y: 0.0,
+                radius: 1.0,
+            },
+            true,
         );
+        assert_eq!(32, circle.outline().len());
+    }
+
+    #[test]
+    fn level_name_and_timings_accessors() {
+        let mut level = build_default_level();
+        assert_eq!("My level", level.name());
+
+        level.set_name("Renamed");
+        level.set_timings([7, 9]);
+        assert_eq!("Renamed", level.name());
+        assert_eq!(Timings { gold: 7, silver: 9 }, level.timings());
+
+        let json = level.to_json().unwrap();
+        assert!(json.starts_with(r#"{"name":"Renamed","timings":[7,9],"#));
+        assert_eq!(level, Level::from_json(&json).unwrap());
+    }
+
+    #[test]
+    fn shape_with_repeated_vertices() {
+        let vertices = vec![
+            Point::new(0.0, 0.0),
+            Point::new(0.0, 10.0),
+            Point::new(10.0, 10.0),
+            Point::new(10.0, 10.0),
+            Point::new(10.0, 0.0),
+            Point::new(0.0, 0.0),
+        ];
+        let square = Shape::Polygon { vertices };
+        assert!(square.contains_point(&Point::new(5.0, 5.0)));
+        assert!(square.contains_point(&Point::new(10.0, 10.0)));
+        assert!(!square.contains_point(&Point::new(500.0, 500.0)));
+        assert!(square.is_simple());
+        assert_eq!(Some(true), square.is_convex());
+        assert!(!square.intersects_segment(&Point::new(-999.0, 3.0), &Point::new(-900.0, 3.0)));
+
+        let level = Level::with_entities(String::new(), [0, 0], [Entity::new_deadly(square, true)]);
+        assert!(level.entities_at(&Point::new(-999.0, 3.0)).is_empty());
+        assert_eq!(vec![0], level.entities_at(&Point::new(1.0, 3.0)));
+    }
+
+    #[test]
+    fn short_edges_use_an_absolute_tolerance() {
+        let sliver = Shape::Polygon {
+            vertices: vec![
+                Point::new(0.0, 0.0),
+                Point::new(0.001, 0.0),
+                Point::new(0.0, 0.001),
+            ],
+        };
+        assert!(!sliver.contains_point(&Point::new(0.0005, -0.0001)));
+        assert!(sliver.contains_point(&Point::new(0.0005, 0.0)));
     }
 }